@@ -1,11 +1,14 @@
+use std::time::Duration;
+
 use bevy_reflect::prelude::*;
-#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "bevy_spritesheet_animation")]
+use thiserror::Error;
 
 /// The duration of an animation or clip.
-#[derive(Debug, Clone, Copy, Reflect)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Reflect)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[reflect(Debug)]
+#[reflect(Debug, PartialEq)]
 #[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
 pub enum AnimationDuration {
     PerFrame(u32),
@@ -41,13 +44,16 @@ impl From<AnimationDuration> for bevy_spritesheet_animation::animation::Animatio
 }
 
 /// The repeat behavior of an animation.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[reflect(Debug, Default, Hash, PartialEq)]
-#[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
+///
+/// Unlike the other animation types, this is always serializable because it is
+/// used in loader settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Reflect, Serialize)]
+#[reflect(Debug, Default, Hash, PartialEq, Deserialize, Serialize)]
 pub enum AnimationRepeat {
+    /// Repeats the animation forever.
     #[default]
     Loop,
+    /// Plays the animation the given number of times, and at least once.
     Times(usize),
 }
 
@@ -121,10 +127,23 @@ impl From<AnimationDirection> for bevy_spritesheet_animation::animation::Animati
     }
 }
 
+/// A named point of interest on a frame of a clip, such as the frame where an
+/// hourglass cursor flips over.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Reflect)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[reflect(Debug, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
+pub struct AnimationMarker {
+    /// The name of the marker.
+    pub name: String,
+    /// The index of the frame in the clip's `atlas_indices`.
+    pub frame: usize,
+}
+
 /// A clip in an animation.
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, PartialEq, Reflect)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[reflect(Debug)]
+#[reflect(Debug, PartialEq)]
 #[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
 pub struct AnimationClip {
     /// The indices of the frames in the atlas that make up the clip.
@@ -134,12 +153,24 @@ pub struct AnimationClip {
     /// The direction of the clip.
     #[cfg_attr(feature = "serde", serde(default))]
     pub direction: AnimationDirection,
+    /// The number of times the clip is played before moving on to the next
+    /// clip. `None` plays the clip once, and so does `Some(0)`, since a clip
+    /// can't be skipped.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub repeat: Option<usize>,
+    /// The markers on frames of the clip.
+    ///
+    /// An [`AnimatedCursorPlayer`](crate::player::AnimatedCursorPlayer)
+    /// triggers a [`CursorMarkerHit`](crate::player::CursorMarkerHit) event for
+    /// each marker of a frame whenever it displays the frame.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub markers: Vec<AnimationMarker>,
 }
 
 /// An animation.
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, PartialEq, Reflect)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[reflect(Debug)]
+#[reflect(Debug, PartialEq)]
 #[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
 pub struct Animation {
     /// The clips that make up the animation.
@@ -151,3 +182,764 @@ pub struct Animation {
     #[cfg_attr(feature = "serde", serde(default))]
     pub direction: AnimationDirection,
 }
+
+/// A frame of an animation, resolved from its clips.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub struct AnimationFrame {
+    /// The index of the clip that the frame belongs to.
+    pub clip_index: usize,
+    /// The index of the frame in the clip's `atlas_indices`.
+    pub frame_index: usize,
+    /// The repetition of the clip that the frame belongs to.
+    ///
+    /// When a ping-pong animation plays a clip backwards, its repetitions are
+    /// numbered after the forward ones.
+    pub clip_repetition: usize,
+    /// The index of the frame in the atlas.
+    pub atlas_index: usize,
+    /// How long the frame is displayed for.
+    pub duration: Duration,
+}
+
+/// Returns `items` in the given direction.
+///
+/// A ping-pong plays forwards and then backwards without repeating the first
+/// and last items, so that it can loop without displaying them twice in a row.
+fn apply_direction<T: Copy>(items: &[T], direction: AnimationDirection) -> Vec<T> {
+    match direction {
+        AnimationDirection::Forwards => items.to_vec(),
+        AnimationDirection::Backwards => items.iter().rev().copied().collect(),
+        AnimationDirection::PingPong => items
+            .iter()
+            .chain(
+                items
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .take(items.len().saturating_sub(2)),
+            )
+            .copied()
+            .collect(),
+    }
+}
+
+impl AnimationClip {
+    /// Returns the number of times the clip is played, which is at least 1.
+    pub fn repetitions(&self) -> usize {
+        self.repeat.unwrap_or(1).max(1)
+    }
+
+    /// Returns the frames of the clip, including all of its repetitions.
+    ///
+    /// A `duration` of [`AnimationDuration::PerRepetition`] is the duration of
+    /// one repetition. `clip_index` is the index of the clip in its animation.
+    pub fn frames(&self, clip_index: usize) -> Vec<AnimationFrame> {
+        let frame_indices = apply_direction(
+            &(0..self.atlas_indices.len()).collect::<Vec<_>>(),
+            self.direction,
+        );
+
+        let duration = match self.duration {
+            AnimationDuration::PerFrame(millis) => Duration::from_millis(millis as u64),
+            AnimationDuration::PerRepetition(millis) => {
+                Duration::from_millis(millis as u64) / frame_indices.len().max(1) as u32
+            }
+        };
+
+        frame_indices
+            .iter()
+            .cycle()
+            .take(frame_indices.len() * self.repetitions())
+            .enumerate()
+            .map(|(i, &frame_index)| AnimationFrame {
+                clip_index,
+                frame_index,
+                clip_repetition: i / frame_indices.len(),
+                atlas_index: self.atlas_indices[frame_index],
+                duration,
+            })
+            .collect()
+    }
+}
+
+impl Animation {
+    /// Returns the frames of one repetition of the animation.
+    ///
+    /// Playing the animation backwards reverses the order of the clips and the
+    /// order of the frames within them. A ping-pong plays forwards and then
+    /// backwards without repeating the first and last frames, so the frames of
+    /// `[0, 1, 2]` are `[0, 1, 2, 1]`.
+    pub fn frames(&self) -> Vec<AnimationFrame> {
+        let frames = self
+            .clips
+            .iter()
+            .enumerate()
+            .flat_map(|(i, clip)| clip.frames(i))
+            .collect::<Vec<_>>();
+        let forward_len = frames.len();
+
+        let mut frames = apply_direction(&frames, self.direction);
+        if self.direction == AnimationDirection::PingPong {
+            for frame in &mut frames[forward_len..] {
+                let repeat = self.clips[frame.clip_index].repetitions();
+                frame.clip_repetition = 2 * repeat - 1 - frame.clip_repetition;
+            }
+        }
+        frames
+    }
+
+    /// Returns an iterator over the frames of the animation, including all of
+    /// its repetitions.
+    ///
+    /// The iterator never ends if the animation loops.
+    pub fn timeline(&self) -> AnimationTimeline {
+        AnimationTimeline {
+            frames: self.frames(),
+            repeat: self.repeat,
+            step: 0,
+            repetition: 0,
+        }
+    }
+
+    /// Returns the duration of one repetition of the animation.
+    pub fn repetition_duration(&self) -> Duration {
+        self.frames().iter().map(|f| f.duration).sum()
+    }
+
+    /// Returns the duration of the whole animation, or `None` if it loops.
+    pub fn duration(&self) -> Option<Duration> {
+        match self.repeat {
+            AnimationRepeat::Loop => None,
+            AnimationRepeat::Times(times) => u32::try_from(times.max(1))
+                .ok()
+                .and_then(|times| self.repetition_duration().checked_mul(times)),
+        }
+    }
+
+    /// Returns the frame that is displayed `time` after the animation starts,
+    /// or `None` if the animation has no frames.
+    ///
+    /// Once an animation that doesn't loop has finished, its last frame stays
+    /// displayed.
+    pub fn sample(&self, time: Duration) -> Option<AnimationFrame> {
+        let frames = self.frames();
+        let last = *frames.last()?;
+
+        let repetition_duration: Duration = frames.iter().map(|f| f.duration).sum();
+        if repetition_duration.is_zero() {
+            return frames.first().copied();
+        }
+
+        if self.duration().is_some_and(|duration| time >= duration) {
+            return Some(last);
+        }
+
+        let mut time =
+            Duration::from_nanos((time.as_nanos() % repetition_duration.as_nanos()) as u64);
+
+        for frame in frames {
+            if time < frame.duration {
+                return Some(frame);
+            }
+            time -= frame.duration;
+        }
+
+        Some(last)
+    }
+
+    /// Creates a looping animation from `(atlas_index, duration)` steps, where
+    /// `duration` is in milliseconds.
+    ///
+    /// Consecutive steps with the same duration are grouped into a single clip
+    /// so that each step is displayed for its own duration.
+    pub fn from_frame_durations(steps: impl IntoIterator<Item = (usize, u32)>) -> Self {
+        let mut clips: Vec<AnimationClip> = Vec::new();
+
+        for (atlas_index, duration) in steps {
+            match clips.last_mut() {
+                Some(clip) if clip.duration == AnimationDuration::PerFrame(duration) => {
+                    clip.atlas_indices.push(atlas_index);
+                }
+                _ => clips.push(AnimationClip {
+                    atlas_indices: vec![atlas_index],
+                    duration: AnimationDuration::PerFrame(duration),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                }),
+            }
+        }
+
+        Self {
+            clips,
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        }
+    }
+}
+
+/// An iterator over the frames of an animation, including all of its
+/// repetitions.
+///
+/// Created by [`Animation::timeline`].
+#[derive(Debug, Clone)]
+pub struct AnimationTimeline {
+    frames: Vec<AnimationFrame>,
+    repeat: AnimationRepeat,
+    step: usize,
+    repetition: usize,
+}
+
+impl AnimationTimeline {
+    /// Returns the number of completed repetitions.
+    #[inline(always)]
+    pub fn repetition(&self) -> usize {
+        self.repetition
+    }
+}
+
+impl Iterator for AnimationTimeline {
+    type Item = AnimationFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let AnimationRepeat::Times(times) = self.repeat {
+            if self.repetition >= times.max(1) {
+                return None;
+            }
+        }
+
+        let frame = *self.frames.get(self.step)?;
+
+        self.step += 1;
+        if self.step == self.frames.len() {
+            self.step = 0;
+            self.repetition += 1;
+        }
+
+        Some(frame)
+    }
+}
+
+/// The frame duration that `bevy_spritesheet_animation` uses for clips and
+/// animations that don't set one.
+#[cfg(feature = "bevy_spritesheet_animation")]
+const SPRITESHEET_DEFAULT_DURATION: AnimationDuration = AnimationDuration::PerFrame(100);
+
+/// Possible errors that can be produced when converting an animation to a
+/// `bevy_spritesheet_animation` animation.
+#[cfg(feature = "bevy_spritesheet_animation")]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SpritesheetAnimationError {
+    /// A clip has an atlas index that is outside of the spritesheet.
+    #[error("atlas index {index} of clip {clip_index} is outside of the spritesheet's {cell_count} cells")]
+    AtlasIndexOutOfBounds {
+        /// The index of the clip in the animation.
+        clip_index: usize,
+        /// The atlas index.
+        index: usize,
+        /// The number of cells in the spritesheet.
+        cell_count: usize,
+    },
+}
+
+#[cfg(feature = "bevy_spritesheet_animation")]
+impl AnimationClip {
+    /// Converts the clip to a `bevy_spritesheet_animation` animation that loops
+    /// the clip, since clips can only be created as part of an animation.
+    ///
+    /// See [`Animation::to_spritesheet_animation`].
+    pub fn to_spritesheet_animation(
+        &self,
+        spritesheet: &bevy_spritesheet_animation::prelude::Spritesheet,
+    ) -> Result<bevy_spritesheet_animation::animation::Animation, SpritesheetAnimationError> {
+        Animation {
+            clips: vec![self.clone()],
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        }
+        .to_spritesheet_animation(spritesheet)
+    }
+}
+
+#[cfg(feature = "bevy_spritesheet_animation")]
+impl From<&bevy_spritesheet_animation::clip::Clip> for AnimationClip {
+    /// Converts a `bevy_spritesheet_animation` clip.
+    ///
+    /// Clips without a duration use the default duration of
+    /// `bevy_spritesheet_animation`, which is 100 ms per frame. Clip markers are
+    /// not converted.
+    fn from(clip: &bevy_spritesheet_animation::clip::Clip) -> Self {
+        AnimationClip {
+            atlas_indices: clip.atlas_indices().to_vec(),
+            duration: clip
+                .duration()
+                .map(AnimationDuration::from)
+                .unwrap_or(SPRITESHEET_DEFAULT_DURATION),
+            direction: clip
+                .direction()
+                .map(AnimationDirection::from)
+                .unwrap_or_default(),
+            repeat: *clip.repetitions(),
+            markers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "bevy_spritesheet_animation")]
+impl Animation {
+    /// Converts the animation to a `bevy_spritesheet_animation` animation.
+    ///
+    /// Clips keep their atlas indices, which are indices into the cells of
+    /// `spritesheet`, so all of them must be smaller than its number of columns
+    /// times its number of rows. Clip markers are not converted, since
+    /// `bevy_spritesheet_animation` identifies markers by opaque IDs rather than
+    /// names.
+    pub fn to_spritesheet_animation(
+        &self,
+        spritesheet: &bevy_spritesheet_animation::prelude::Spritesheet,
+    ) -> Result<bevy_spritesheet_animation::animation::Animation, SpritesheetAnimationError> {
+        // The builder logs and drops indices outside of the spritesheet, which
+        // would silently change the animation.
+        let cell_count = spritesheet.columns() * spritesheet.rows();
+        for (clip_index, clip) in self.clips.iter().enumerate() {
+            if let Some(&index) = clip
+                .atlas_indices
+                .iter()
+                .find(|&&index| index >= cell_count)
+            {
+                return Err(SpritesheetAnimationError::AtlasIndexOutOfBounds {
+                    clip_index,
+                    index,
+                    cell_count,
+                });
+            }
+        }
+
+        let mut builder = spritesheet
+            .create_animation()
+            .set_repetitions(self.repeat.into())
+            .set_direction(self.direction.into());
+
+        for (i, clip) in self.clips.iter().enumerate() {
+            if i > 0 {
+                builder = builder.start_clip();
+            }
+
+            builder = builder
+                .add_indices(clip.atlas_indices.iter().copied())
+                .set_clip_duration(clip.duration.into())
+                .set_clip_direction(clip.direction.into());
+
+            if let Some(repeat) = clip.repeat {
+                builder = builder.set_clip_repetitions(repeat.max(1));
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+#[cfg(feature = "bevy_spritesheet_animation")]
+impl From<&bevy_spritesheet_animation::animation::Animation> for Animation {
+    /// Converts a `bevy_spritesheet_animation` animation.
+    ///
+    /// Clips without a duration use the animation's duration. A duration of
+    /// [`AnimationDuration::PerRepetition`] on the animation is spread evenly
+    /// over the frames of all of its clips. Clip markers are not converted.
+    fn from(animation: &bevy_spritesheet_animation::animation::Animation) -> Self {
+        let mut clips = animation
+            .clips()
+            .iter()
+            .map(AnimationClip::from)
+            .collect::<Vec<_>>();
+
+        let mut result = Self {
+            clips: Vec::new(),
+            repeat: animation
+                .repetitions()
+                .map(AnimationRepeat::from)
+                .unwrap_or_default(),
+            direction: animation
+                .direction()
+                .map(AnimationDirection::from)
+                .unwrap_or_default(),
+        };
+
+        let duration = match animation.duration().map(AnimationDuration::from) {
+            Some(AnimationDuration::PerRepetition(millis)) => {
+                result.clips = clips.clone();
+                let frame_count = result.frames().len().max(1) as u32;
+                Some(AnimationDuration::PerFrame(millis / frame_count))
+            }
+            duration => duration,
+        };
+
+        for (clip, source) in clips.iter_mut().zip(animation.clips()) {
+            if source.duration().is_none() {
+                if let Some(duration) = duration {
+                    clip.duration = duration;
+                }
+            }
+        }
+
+        result.clips = clips;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_frame_durations() {
+        let animation =
+            Animation::from_frame_durations([(0, 100), (1, 100), (2, 500), (1, 100), (0, 100)]);
+
+        assert_eq!(
+            animation.clips,
+            vec![
+                AnimationClip {
+                    atlas_indices: vec![0, 1],
+                    duration: AnimationDuration::PerFrame(100),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+                AnimationClip {
+                    atlas_indices: vec![2],
+                    duration: AnimationDuration::PerFrame(500),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+                AnimationClip {
+                    atlas_indices: vec![1, 0],
+                    duration: AnimationDuration::PerFrame(100),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+            ]
+        );
+        assert_eq!(animation.repeat, AnimationRepeat::Loop);
+        assert_eq!(animation.direction, AnimationDirection::Forwards);
+    }
+
+    #[test]
+    fn test_clip_repeat() {
+        let animation = Animation {
+            clips: vec![
+                AnimationClip {
+                    atlas_indices: vec![0, 1, 2],
+                    duration: AnimationDuration::PerFrame(100),
+                    direction: AnimationDirection::Forwards,
+                    repeat: Some(3),
+                    markers: Vec::new(),
+                },
+                AnimationClip {
+                    atlas_indices: vec![3, 4, 5],
+                    duration: AnimationDuration::PerRepetition(300),
+                    direction: AnimationDirection::PingPong,
+                    repeat: Some(2),
+                    markers: Vec::new(),
+                },
+                AnimationClip {
+                    atlas_indices: vec![6],
+                    duration: AnimationDuration::PerFrame(500),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+            ],
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        };
+
+        let frames = animation.frames();
+
+        assert_eq!(
+            frames.iter().map(|f| f.atlas_index).collect::<Vec<_>>(),
+            vec![0, 1, 2, 0, 1, 2, 0, 1, 2, 3, 4, 5, 4, 3, 4, 5, 4, 6]
+        );
+        assert_eq!(
+            frames.iter().map(|f| f.clip_index).collect::<Vec<_>>(),
+            vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2]
+        );
+        assert_eq!(
+            frames.iter().map(|f| f.frame_index).collect::<Vec<_>>(),
+            vec![0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 1, 0, 1, 2, 1, 0]
+        );
+        assert_eq!(
+            frames.iter().map(|f| f.clip_repetition).collect::<Vec<_>>(),
+            vec![0, 0, 0, 1, 1, 1, 2, 2, 2, 0, 0, 0, 0, 1, 1, 1, 1, 0]
+        );
+        assert!(frames[..9]
+            .iter()
+            .all(|f| f.duration == Duration::from_millis(100)));
+        assert!(frames[9..17]
+            .iter()
+            .all(|f| f.duration == Duration::from_millis(75)));
+        assert_eq!(frames[17].duration, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_clip_repeat_zero() {
+        let mut zero = clip(vec![0, 1], AnimationDirection::Forwards);
+        zero.repeat = Some(0);
+        assert_eq!(zero.repetitions(), 1);
+
+        let animation = Animation {
+            clips: vec![zero, clip(vec![2], AnimationDirection::Forwards)],
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        };
+        assert_eq!(
+            animation
+                .frames()
+                .iter()
+                .map(|f| f.atlas_index)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    fn clip(atlas_indices: Vec<usize>, direction: AnimationDirection) -> AnimationClip {
+        AnimationClip {
+            atlas_indices,
+            duration: AnimationDuration::PerFrame(100),
+            direction,
+            repeat: None,
+            markers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_timeline() {
+        let atlas_indices = |animation: &Animation| {
+            animation
+                .timeline()
+                .take(12)
+                .map(|f| f.atlas_index)
+                .collect::<Vec<_>>()
+        };
+
+        let mut animation = Animation {
+            clips: vec![
+                clip(vec![0, 1, 2], AnimationDirection::Forwards),
+                clip(vec![3, 4], AnimationDirection::Backwards),
+            ],
+            repeat: AnimationRepeat::Times(2),
+            direction: AnimationDirection::Forwards,
+        };
+        assert_eq!(
+            atlas_indices(&animation),
+            vec![0, 1, 2, 4, 3, 0, 1, 2, 4, 3]
+        );
+
+        animation.direction = AnimationDirection::Backwards;
+        assert_eq!(
+            atlas_indices(&animation),
+            vec![3, 4, 2, 1, 0, 3, 4, 2, 1, 0]
+        );
+
+        // Ping-pong edge frames are not duplicated, including between
+        // repetitions.
+        animation.direction = AnimationDirection::PingPong;
+        animation.repeat = AnimationRepeat::Loop;
+        assert_eq!(
+            atlas_indices(&animation),
+            vec![0, 1, 2, 4, 3, 4, 2, 1, 0, 1, 2, 4]
+        );
+
+        let animation = Animation {
+            clips: vec![clip(vec![0, 1, 2], AnimationDirection::PingPong)],
+            repeat: AnimationRepeat::Times(2),
+            direction: AnimationDirection::Forwards,
+        };
+        assert_eq!(atlas_indices(&animation), vec![0, 1, 2, 1, 0, 1, 2, 1]);
+
+        let animation = Animation {
+            clips: vec![clip(vec![5], AnimationDirection::PingPong)],
+            repeat: AnimationRepeat::Times(0),
+            direction: AnimationDirection::PingPong,
+        };
+        assert_eq!(atlas_indices(&animation), vec![5]);
+    }
+
+    #[test]
+    fn test_sample() {
+        let animation = Animation {
+            clips: vec![
+                clip(vec![0, 1], AnimationDirection::Forwards),
+                AnimationClip {
+                    atlas_indices: vec![2],
+                    duration: AnimationDuration::PerFrame(500),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+            ],
+            repeat: AnimationRepeat::Times(2),
+            direction: AnimationDirection::Forwards,
+        };
+
+        let sample = |millis| {
+            animation
+                .sample(Duration::from_millis(millis))
+                .map(|f| (f.clip_index, f.atlas_index))
+        };
+
+        assert_eq!(animation.repetition_duration(), Duration::from_millis(700));
+        assert_eq!(animation.duration(), Some(Duration::from_millis(1400)));
+
+        assert_eq!(sample(0), Some((0, 0)));
+        assert_eq!(sample(99), Some((0, 0)));
+        assert_eq!(sample(100), Some((0, 1)));
+        assert_eq!(sample(200), Some((1, 2)));
+        assert_eq!(sample(699), Some((1, 2)));
+        assert_eq!(sample(700), Some((0, 0)));
+        assert_eq!(sample(1399), Some((1, 2)));
+        assert_eq!(sample(5000), Some((1, 2)));
+
+        let empty = Animation {
+            clips: Vec::new(),
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        };
+        assert_eq!(empty.sample(Duration::ZERO), None);
+        assert_eq!(empty.timeline().next(), None);
+    }
+
+    #[cfg(feature = "bevy_spritesheet_animation")]
+    #[test]
+    fn test_spritesheet_round_trip() {
+        use bevy_spritesheet_animation::prelude::Spritesheet;
+
+        let spritesheet = Spritesheet::new(&Default::default(), 4, 4);
+
+        let animation = Animation {
+            clips: vec![
+                AnimationClip {
+                    atlas_indices: vec![0, 1, 2],
+                    duration: AnimationDuration::PerFrame(75),
+                    direction: AnimationDirection::Forwards,
+                    repeat: Some(3),
+                    markers: Vec::new(),
+                },
+                AnimationClip {
+                    atlas_indices: vec![3, 4],
+                    duration: AnimationDuration::PerRepetition(300),
+                    direction: AnimationDirection::Backwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+                AnimationClip {
+                    atlas_indices: vec![5, 6, 7],
+                    duration: AnimationDuration::PerFrame(100),
+                    direction: AnimationDirection::PingPong,
+                    repeat: Some(2),
+                    markers: Vec::new(),
+                },
+            ],
+            repeat: AnimationRepeat::Times(2),
+            direction: AnimationDirection::Backwards,
+        };
+
+        let converted = animation.to_spritesheet_animation(&spritesheet).unwrap();
+        assert_eq!(
+            *converted.repetitions(),
+            Some(bevy_spritesheet_animation::prelude::AnimationRepeat::Times(
+                2
+            ))
+        );
+        assert_eq!(
+            *converted.direction(),
+            Some(bevy_spritesheet_animation::prelude::AnimationDirection::Backwards)
+        );
+        assert_eq!(converted.clips().len(), 3);
+        assert_eq!(converted.clips()[1].atlas_indices(), &[3, 4]);
+        assert_eq!(*converted.clips()[0].repetitions(), Some(3));
+        assert_eq!(*converted.clips()[1].repetitions(), None);
+        assert_eq!(
+            converted.clips()[1].duration().map(AnimationDuration::from),
+            Some(AnimationDuration::PerRepetition(300))
+        );
+        assert_eq!(
+            *converted.clips()[1].direction(),
+            Some(bevy_spritesheet_animation::prelude::AnimationDirection::Backwards)
+        );
+        assert_eq!(
+            *converted.clips()[2].direction(),
+            Some(bevy_spritesheet_animation::prelude::AnimationDirection::PingPong)
+        );
+
+        assert_eq!(Animation::from(&converted), animation);
+
+        // A single clip converts to an animation that loops it.
+        let converted = animation.clips[1]
+            .to_spritesheet_animation(&spritesheet)
+            .unwrap();
+        assert_eq!(converted.clips().len(), 1);
+        assert_eq!(
+            AnimationClip::from(&converted.clips()[0]),
+            animation.clips[1]
+        );
+        assert_eq!(Animation::from(&converted).repeat, AnimationRepeat::Loop);
+
+        // A duration per repetition of the whole animation is spread over the
+        // frames of clips that don't have their own duration.
+        let converted = spritesheet
+            .create_animation()
+            .set_duration(
+                bevy_spritesheet_animation::prelude::AnimationDuration::PerRepetition(400),
+            )
+            .add_indices([0, 1])
+            .start_clip()
+            .add_indices([2, 3])
+            .set_clip_duration(bevy_spritesheet_animation::prelude::AnimationDuration::PerFrame(50))
+            .build();
+        let animation = Animation::from(&converted);
+        assert_eq!(
+            animation.clips[0].duration,
+            AnimationDuration::PerFrame(100)
+        );
+        assert_eq!(animation.clips[1].duration, AnimationDuration::PerFrame(50));
+        assert_eq!(animation.repeat, AnimationRepeat::Loop);
+
+        // Clips without a duration of their own or on the animation use the
+        // default duration.
+        let converted = spritesheet.create_animation().add_indices([0, 1]).build();
+        assert_eq!(
+            AnimationClip::from(&converted.clips()[0]).duration,
+            SPRITESHEET_DEFAULT_DURATION
+        );
+    }
+
+    #[cfg(feature = "bevy_spritesheet_animation")]
+    #[test]
+    fn test_spritesheet_out_of_bounds() {
+        use bevy_spritesheet_animation::prelude::Spritesheet;
+
+        let spritesheet = Spritesheet::new(&Default::default(), 2, 2);
+        let animation = Animation {
+            clips: vec![
+                clip(vec![0, 3], AnimationDirection::Forwards),
+                clip(vec![1, 4, 2], AnimationDirection::Forwards),
+            ],
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        };
+
+        assert!(matches!(
+            animation.to_spritesheet_animation(&spritesheet),
+            Err(SpritesheetAnimationError::AtlasIndexOutOfBounds {
+                clip_index: 1,
+                index: 4,
+                cell_count: 4,
+            })
+        ));
+    }
+}
@@ -1,20 +1,29 @@
+use std::collections::BTreeMap;
+
 use bevy_app::prelude::*;
 use bevy_asset::{io::Reader, prelude::*, AssetLoader, LoadContext, RenderAssetUsages};
 use bevy_image::{Image, TextureAtlasBuilder, TextureAtlasBuilderError, TextureAtlasLayout};
+use bevy_math::UVec2;
 use bevy_reflect::prelude::*;
-use ico::ResourceType;
+use ico::{IconDir, ResourceType};
 use image::{DynamicImage, ImageBuffer};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     ani::{
         decoder::{DecodeError, Decoder},
-        AnimatedCursorMetadata,
+        AnimatedCursorInfo, AnimatedCursorMetadata,
     },
     hotspot::CursorHotspots,
+    variant::{
+        best_size_index, best_variant, size_for_scale_factor, CursorVariant, DEFAULT_CURSOR_SIZE,
+    },
 };
 
-use super::animation::*;
+use super::animation::{Animation, AnimationRepeat};
+#[cfg(feature = "packed_asset")]
+use super::packed_asset::PackedAnimatedCursorLoader;
 #[cfg(feature = "serde_json_asset")]
 use super::serde_asset::JsonDeserializer;
 #[cfg(feature = "serde_ron_asset")]
@@ -50,6 +59,9 @@ impl Plugin for AnimatedCursorAssetPlugin {
             }
         }
 
+        #[cfg(feature = "packed_asset")]
+        app.init_asset_loader::<PackedAnimatedCursorLoader>();
+
         app.init_asset::<AnimatedCursor>()
             .init_asset_loader::<AnimatedCursorLoader>()
             .register_asset_reflect::<AnimatedCursor>();
@@ -61,24 +73,93 @@ impl Plugin for AnimatedCursorAssetPlugin {
 pub struct AnimatedCursor {
     /// The metadata for the animated cursor. This is optional and only set for
     /// .ANI files.
-    pub(super) metadata: Option<AnimatedCursorMetadata>,
+    pub(crate) metadata: Option<AnimatedCursorMetadata>,
+    /// The descriptive information for the animated cursor. This is optional
+    /// and only set for .ANI files that have an 'INFO' list.
+    pub(crate) info: Option<AnimatedCursorInfo>,
     /// A handle to the image asset.
     pub image: Handle<Image>,
     /// A handle to the texture atlas layout asset.
     pub texture_atlas_layout: Handle<TextureAtlasLayout>,
     /// The hotspot data.
     pub hotspots: CursorHotspots,
-    /// The animation to play.
+    /// The resolution variants of the cursor, with the variant used by the
+    /// animation first.
+    ///
+    /// Empty if the sizes are not known, e.g. for cursors loaded from text
+    /// formats.
+    pub variants: Vec<CursorVariant>,
+    /// The animation to play by default.
     pub animation: Animation,
+    /// Additional animations that can be switched to by name.
+    ///
+    /// Only text formats can define these.
+    pub animations: BTreeMap<String, Animation>,
 }
 
 impl AnimatedCursor {
+    /// Returns the metadata for the animated cursor, if it was loaded from an
+    /// .ANI file.
+    #[inline(always)]
+    pub fn metadata(&self) -> Option<&AnimatedCursorMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns the descriptive information for the animated cursor, if it was
+    /// loaded from an .ANI file that has any.
+    #[inline(always)]
+    pub fn info(&self) -> Option<&AnimatedCursorInfo> {
+        self.info.as_ref()
+    }
+
+    /// Returns the title of the animated cursor, if it has one.
+    #[inline(always)]
+    pub fn title(&self) -> Option<&str> {
+        self.info.as_ref()?.title.as_deref()
+    }
+
+    /// Returns the author of the animated cursor, if it has one.
+    #[inline(always)]
+    pub fn artist(&self) -> Option<&str> {
+        self.info.as_ref()?.artist.as_deref()
+    }
+
     /// Returns the hotspot for the cursor at the given index, or `(0, 0)` if
     /// the index is out of bounds.
     #[inline(always)]
     pub fn hotspot_or_default(&self, index: usize) -> (u16, u16) {
         self.hotspots.get_or_default(index)
     }
+
+    /// Returns the animation with the given name, or `None` if there is no
+    /// such animation.
+    pub fn named_animation(&self, name: &str) -> Option<&Animation> {
+        self.animations.get(name)
+    }
+
+    /// Returns the animation with the given name, or the default animation if
+    /// `name` is `None` or there is no such animation.
+    pub fn animation_or_default(&self, name: Option<&str>) -> &Animation {
+        name.and_then(|name| self.named_animation(name))
+            .unwrap_or(&self.animation)
+    }
+
+    /// Returns the variant that best matches `size`, in pixels, or `None` if
+    /// the cursor has no variants.
+    ///
+    /// Add a frame index to the variant's `atlas_offset` to get the frame's
+    /// atlas index at that size.
+    pub fn variant_for_size(&self, size: u32) -> Option<&CursorVariant> {
+        best_variant(&self.variants, size)
+    }
+
+    /// Returns the variant that best suits a window with the given scale
+    /// factor, or `None` if the cursor has no variants.
+    ///
+    /// See [`size_for_scale_factor`] for how the scale factor maps to a size.
+    pub fn variant_for_scale_factor(&self, scale_factor: f32) -> Option<&CursorVariant> {
+        self.variant_for_size(size_for_scale_factor(scale_factor))
+    }
 }
 
 /// A loader for animated cursor assets from .ANI files.
@@ -86,6 +167,51 @@ impl AnimatedCursor {
 #[reflect(Debug, Default)]
 pub struct AnimatedCursorLoader;
 
+/// Settings for loading animated cursors with [`AnimatedCursorLoader`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AnimatedCursorLoaderSettings {
+    /// The size, in pixels, of the variant that the animation plays at.
+    ///
+    /// Defaults to [`DEFAULT_CURSOR_SIZE`].
+    pub preferred_size: u32,
+    /// Whether to load every size of multi-resolution frames as a variant. If
+    /// `false`, only the variant closest to `preferred_size` is loaded.
+    ///
+    /// Only sizes that every frame has are loaded, so that the images of a
+    /// variant are all of its size.
+    ///
+    /// Defaults to `true`.
+    pub load_all_sizes: bool,
+    /// The usage of the texture atlas image.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+    /// Whether to keep the image of each frame as an `image_{index}` labeled
+    /// asset, where `index` is the frame's atlas index.
+    ///
+    /// Defaults to `true`.
+    pub keep_frame_images: bool,
+    /// If set, overrides the duration of every frame, in milliseconds.
+    pub frame_duration: Option<u32>,
+    /// If set, overrides the repeat behavior of the animation. .ANI files loop
+    /// by default.
+    pub repeat: Option<AnimationRepeat>,
+}
+
+impl Default for AnimatedCursorLoaderSettings {
+    fn default() -> Self {
+        Self {
+            preferred_size: DEFAULT_CURSOR_SIZE,
+            load_all_sizes: true,
+            asset_usage: RenderAssetUsages::default(),
+            keep_frame_images: true,
+            frame_duration: None,
+            repeat: None,
+        }
+    }
+}
+
 /// Possible errors that can be produced by [`AnimatedCursorLoader`].
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -96,8 +222,10 @@ pub enum AnimatedCursorLoaderError {
     /// A [DecodeError] error.
     #[error("could not decode animated cursor: {0}")]
     DecodeError(#[from] DecodeError),
-    #[error("unsupported frame entry count: {0} (expected 1)")]
-    UnsupportedFrameEntryCount(usize),
+    #[error("frame has no entries")]
+    MissingFrameEntry,
+    #[error("frames have no entry size in common")]
+    NoCommonFrameSize,
     #[error("resource type must be cursor")]
     InvalidResourceType,
     #[error("missing hotspot")]
@@ -110,12 +238,12 @@ pub enum AnimatedCursorLoaderError {
 
 impl AssetLoader for AnimatedCursorLoader {
     type Asset = AnimatedCursor;
-    type Settings = ();
+    type Settings = AnimatedCursorLoaderSettings;
     type Error = AnimatedCursorLoaderError;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
@@ -151,24 +279,63 @@ impl AssetLoader for AnimatedCursorLoader {
 
         let c = decoder.decode()?;
 
-        let items = c
-            .frames
+        // Frames can contain the same image at several sizes. Every size that
+        // all frames have becomes a variant, so that the images of a variant
+        // are all of its size. Sizes that some frames lack are skipped. The
+        // variant closest to the preferred size comes first, so the
+        // animation's atlas indices, which are frame indices, refer to it.
+        let entry_sizes = |f: &IconDir| {
+            f.entries()
+                .iter()
+                .map(|e| UVec2::new(e.width(), e.height()))
+                .collect::<Vec<_>>()
+        };
+        if c.frames.iter().any(|f| f.entries().is_empty()) {
+            return Err(AnimatedCursorLoaderError::MissingFrameEntry);
+        }
+        let mut sizes = c.frames.first().map(entry_sizes).unwrap_or_default();
+        sizes.retain(|size| c.frames.iter().all(|f| entry_sizes(f).contains(size)));
+        sizes.sort_by_key(|s| (s.max_element(), s.x, s.y));
+        sizes.dedup();
+        if sizes.is_empty() && !c.frames.is_empty() {
+            return Err(AnimatedCursorLoaderError::NoCommonFrameSize);
+        }
+        if let Some(i) = best_size_index(
+            sizes.iter().map(|s| s.max_element()),
+            settings.preferred_size,
+        ) {
+            let preferred = sizes.remove(i);
+            sizes.insert(0, preferred);
+        }
+        if !settings.load_all_sizes {
+            sizes.truncate(1);
+        }
+
+        let variants = sizes
             .iter()
             .enumerate()
-            .map(|(i, f)| {
-                if f.entries().len() != 1 {
-                    return Err(AnimatedCursorLoaderError::UnsupportedFrameEntryCount(
-                        f.entries().len(),
-                    ));
-                }
+            .map(|(i, size)| CursorVariant {
+                size: *size,
+                atlas_offset: i * c.frames.len(),
+            })
+            .collect::<Vec<_>>();
 
-                let first = f.entries().first().unwrap();
+        let items = sizes
+            .iter()
+            .flat_map(|size| c.frames.iter().map(move |f| (size, f)))
+            .enumerate()
+            .map(|(i, (size, f))| {
+                let entry = f
+                    .entries()
+                    .iter()
+                    .find(|e| UVec2::new(e.width(), e.height()) == *size)
+                    .ok_or(AnimatedCursorLoaderError::MissingFrameEntry)?;
 
-                if first.resource_type() != ResourceType::Cursor {
+                if entry.resource_type() != ResourceType::Cursor {
                     return Err(AnimatedCursorLoaderError::InvalidResourceType);
                 }
 
-                let icon_image = first.decode()?;
+                let icon_image = entry.decode()?;
 
                 let image = ImageBuffer::from_raw(
                     icon_image.width(),
@@ -184,16 +351,16 @@ impl AssetLoader for AnimatedCursorLoader {
                     .cursor_hotspot()
                     .ok_or(AnimatedCursorLoaderError::MissingHotspot)?;
 
-                Ok((
-                    (
-                        load_context.labeled_asset_scope(
-                            format!("image_{}", i).to_string(),
-                            |_| -> Result<Image, AnimatedCursorLoaderError> { Ok(image.clone()) },
-                        )?,
-                        image,
-                    ),
-                    hotspot,
-                ))
+                let handle = if settings.keep_frame_images {
+                    Some(load_context.labeled_asset_scope(
+                        format!("image_{}", i).to_string(),
+                        |_| -> Result<Image, AnimatedCursorLoaderError> { Ok(image.clone()) },
+                    )?)
+                } else {
+                    None
+                };
+
+                Ok(((handle, image), hotspot))
             })
             .collect::<Result<Vec<_>, AnimatedCursorLoaderError>>()?;
 
@@ -202,12 +369,13 @@ impl AssetLoader for AnimatedCursorLoader {
         let mut hotspots = Vec::new();
 
         for ((handle, image), hotspot) in items.iter() {
-            texture_atlas_builder.add_texture(Some(handle.id()), image);
+            texture_atlas_builder.add_texture(handle.as_ref().map(|h| h.id()), image);
 
             hotspots.push(*hotspot);
         }
 
-        let (texture_atlas_layout, _, image) = texture_atlas_builder.build()?;
+        let (texture_atlas_layout, _, mut image) = texture_atlas_builder.build()?;
+        image.asset_usage = settings.asset_usage;
 
         let texture_atlas_layout = load_context.labeled_asset_scope(
             "texture_atlas_layout".to_string(),
@@ -234,22 +402,27 @@ impl AssetLoader for AnimatedCursorLoader {
             ..Default::default()
         };
 
+        let mut animation = match settings.frame_duration {
+            Some(duration) => Animation::from_frame_durations(
+                c.step_frame_indices()
+                    .into_iter()
+                    .map(|index| (index, duration)),
+            ),
+            None => c.animation(),
+        };
+        if let Some(repeat) = settings.repeat {
+            animation.repeat = repeat;
+        }
+
         Ok(AnimatedCursor {
             metadata: Some(c.metadata.clone()),
+            info: c.info.clone(),
             image,
             texture_atlas_layout,
             hotspots,
-            animation: Animation {
-                clips: vec![AnimationClip {
-                    atlas_indices: (0..c.metadata.frame_count as usize).collect(),
-                    duration: AnimationDuration::PerFrame(
-                        c.metadata.duration_per_frame().as_millis() as u32,
-                    ),
-                    direction: AnimationDirection::Forwards,
-                }],
-                repeat: AnimationRepeat::Loop,
-                direction: AnimationDirection::Forwards,
-            },
+            variants,
+            animation,
+            animations: BTreeMap::new(),
         })
     }
 
@@ -257,3 +430,174 @@ impl AssetLoader for AnimatedCursorLoader {
         &["ANI", "ani"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::testing::{ani_bytes, ani_bytes_with_frame_sizes, create_app, load_with_settings};
+
+    use super::*;
+
+    #[test]
+    fn test_load_with_default_settings() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(2, &[16, 32, 48], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |_| {},
+        )
+        .unwrap();
+
+        let world = app.world();
+        let cursor = world
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        assert_eq!(
+            cursor.variants.iter().map(|v| v.size.x).collect::<Vec<_>>(),
+            vec![DEFAULT_CURSOR_SIZE, 16, 48]
+        );
+        assert_eq!(
+            cursor.animation,
+            Animation::from_frame_durations([(0, 100), (1, 100)])
+        );
+        assert_eq!(
+            world
+                .resource::<Assets<Image>>()
+                .get(&cursor.image)
+                .unwrap()
+                .asset_usage,
+            RenderAssetUsages::default()
+        );
+
+        let image = load_with_settings::<Image, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani#image_1",
+            |_| {},
+        )
+        .unwrap();
+        let image = app.world().resource::<Assets<Image>>().get(&image).unwrap();
+        assert_eq!(image.size(), UVec2::splat(DEFAULT_CURSOR_SIZE));
+    }
+
+    #[test]
+    fn test_load_with_settings() {
+        fn settings(settings: &mut AnimatedCursorLoaderSettings) {
+            settings.preferred_size = 48;
+            settings.load_all_sizes = false;
+            settings.asset_usage = RenderAssetUsages::MAIN_WORLD;
+            settings.keep_frame_images = false;
+            settings.frame_duration = Some(50);
+            settings.repeat = Some(AnimationRepeat::Times(3));
+        }
+
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(2, &[16, 32, 48], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            settings,
+        )
+        .unwrap();
+
+        let world = app.world();
+        let cursor = world
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        assert_eq!(
+            cursor.variants,
+            vec![CursorVariant {
+                size: UVec2::splat(48),
+                atlas_offset: 0,
+            }]
+        );
+        assert_eq!(
+            cursor.animation,
+            Animation {
+                repeat: AnimationRepeat::Times(3),
+                ..Animation::from_frame_durations([(0, 50), (1, 50)])
+            }
+        );
+        assert_eq!(
+            world
+                .resource::<Assets<Image>>()
+                .get(&cursor.image)
+                .unwrap()
+                .asset_usage,
+            RenderAssetUsages::MAIN_WORLD
+        );
+
+        // Frame images aren't kept.
+        assert!(load_with_settings::<Image, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani#image_0",
+            settings,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_load_frames_with_different_sizes() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(
+            Path::new("cursor.ani"),
+            ani_bytes_with_frame_sizes(&[&[32, 64], &[64, 32, 48]], 6),
+        );
+        dir.insert_asset(
+            Path::new("mismatched.ani"),
+            ani_bytes_with_frame_sizes(&[&[32], &[48]], 6),
+        );
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |_| {},
+        )
+        .unwrap();
+
+        let world = app.world();
+        let cursor = world
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        // The 48x48 entry of the second frame has no counterpart in the first.
+        assert_eq!(
+            cursor.variants,
+            vec![
+                CursorVariant {
+                    size: UVec2::splat(32),
+                    atlas_offset: 0,
+                },
+                CursorVariant {
+                    size: UVec2::splat(64),
+                    atlas_offset: 2,
+                },
+            ]
+        );
+        let layout = world
+            .resource::<Assets<TextureAtlasLayout>>()
+            .get(&cursor.texture_atlas_layout)
+            .unwrap();
+        for variant in &cursor.variants {
+            for frame in 0..2 {
+                assert_eq!(
+                    layout.textures[variant.atlas_offset + frame].size(),
+                    variant.size
+                );
+            }
+        }
+
+        let error = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "mismatched.ani",
+            |_| {},
+        )
+        .unwrap_err();
+        assert!(error.contains("frames have no entry size in common"));
+    }
+}
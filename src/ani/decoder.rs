@@ -4,7 +4,7 @@ use std::{
 };
 
 use byteorder::{LittleEndian, ReadBytesExt};
-use ico::IconDir;
+use ico::{IconDir, IconDirEntry, IconImage, ResourceType};
 use riff::{Chunk, ChunkId, LIST_ID};
 
 use super::*;
@@ -18,7 +18,12 @@ pub enum DecodeError {
     MissingHeaderChunk,
     MissingFramesChunk,
     UnsupportedFrameChunkId(ChunkId),
-    UnsupportedRawDataFrameType,
+    InvalidRawDataFrame,
+    UnsupportedRawDataBitCount(u32),
+    UnsupportedRawDataCompression(u32),
+    InvalidSequenceLength(u32),
+    InvalidSequenceFrameIndex(u32),
+    InvalidRateLength(u32),
 }
 
 impl std::error::Error for DecodeError {}
@@ -45,8 +50,25 @@ impl fmt::Display for DecodeError {
             DecodeError::UnsupportedFrameChunkId(id) => {
                 write!(f, "unsupported frame chunk ID: {:?} (expected 'icon')", id)
             }
-            DecodeError::UnsupportedRawDataFrameType => {
-                write!(f, "unsupported raw data frame type")
+            DecodeError::InvalidRawDataFrame => write!(f, "invalid raw data frame"),
+            DecodeError::UnsupportedRawDataBitCount(bit_count) => {
+                write!(f, "unsupported raw data frame bit count: {}", bit_count)
+            }
+            DecodeError::UnsupportedRawDataCompression(compression) => {
+                write!(
+                    f,
+                    "unsupported raw data frame compression: {} (expected BI_RGB)",
+                    compression
+                )
+            }
+            DecodeError::InvalidSequenceLength(len) => {
+                write!(f, "invalid sequence chunk length: {} bytes", len)
+            }
+            DecodeError::InvalidSequenceFrameIndex(index) => {
+                write!(f, "invalid frame index in sequence chunk: {}", index)
+            }
+            DecodeError::InvalidRateLength(len) => {
+                write!(f, "invalid rate chunk length: {} bytes", len)
             }
         }
     }
@@ -120,14 +142,20 @@ impl<R: Read + Seek> Decoder<R> {
 
         let metadata = metadata?;
 
-        let frames = chunks
-            .iter()
-            .find(|c| c.id() == LIST_ID)
-            .map(|c| {
-                if c.read_type(&mut self.reader)? != chunk_id(b"fram") {
-                    return Err(DecodeError::MissingFramesChunk);
-                }
+        let mut frames_chunk = None;
+        let mut info_chunk = None;
 
+        for c in chunks.iter().filter(|c| c.id() == LIST_ID) {
+            let list_type = c.read_type(&mut self.reader)?;
+            if list_type == chunk_id(b"fram") {
+                frames_chunk = Some(c);
+            } else if list_type == chunk_id(b"INFO") {
+                info_chunk = Some(c);
+            }
+        }
+
+        let frames: Vec<IconDir> = frames_chunk
+            .map(|c| {
                 read_chunks(&mut c.iter(&mut self.reader))?
                     .iter()
                     .map(|c| {
@@ -137,12 +165,11 @@ impl<R: Read + Seek> Decoder<R> {
 
                         let contents = c.read_contents(&mut self.reader)?;
 
-                        // TODO: Support raw data frames.
                         if !metadata
                             .flags
                             .contains(AnimatedCursorFlags::ICON_OR_CURSOR_DATA)
                         {
-                            return Err(DecodeError::UnsupportedRawDataFrameType);
+                            return decode_raw_frame(&contents, &metadata);
                         }
 
                         let icon = IconDir::read(&mut std::io::Cursor::new(contents))?;
@@ -154,6 +181,462 @@ impl<R: Read + Seek> Decoder<R> {
             .transpose()?
             .ok_or(DecodeError::MissingFramesChunk)?;
 
-        Ok(AnimatedCursor { metadata, frames })
+        let sequence = chunks
+            .iter()
+            .find(|c| c.id() == chunk_id(b"seq "))
+            .map(|c| {
+                let contents = c.read_contents(&mut self.reader)?;
+                if contents.len() < metadata.step_count as usize * 4 {
+                    return Err(DecodeError::InvalidSequenceLength(contents.len() as u32));
+                }
+
+                let mut cursor = std::io::Cursor::new(contents);
+
+                (0..metadata.step_count)
+                    .map(|_| {
+                        let index = cursor.read_u32::<LittleEndian>()?;
+                        if index as usize >= frames.len() {
+                            return Err(DecodeError::InvalidSequenceFrameIndex(index));
+                        }
+                        Ok(index)
+                    })
+                    .collect::<Result<Vec<_>, DecodeError>>()
+            })
+            .transpose()?;
+
+        let rates = chunks
+            .iter()
+            .find(|c| c.id() == chunk_id(b"rate"))
+            .map(|c| {
+                let contents = c.read_contents(&mut self.reader)?;
+                if contents.len() < metadata.step_count as usize * 4 {
+                    return Err(DecodeError::InvalidRateLength(contents.len() as u32));
+                }
+
+                let mut cursor = std::io::Cursor::new(contents);
+
+                (0..metadata.step_count)
+                    .map(|_| Ok(cursor.read_u32::<LittleEndian>()?))
+                    .collect::<Result<Vec<_>, DecodeError>>()
+            })
+            .transpose()?;
+
+        let info = info_chunk
+            .map(|c| {
+                let mut info = AnimatedCursorInfo::default();
+
+                for c in read_chunks(&mut c.iter(&mut self.reader))? {
+                    let value = if c.id() == chunk_id(b"INAM") {
+                        &mut info.title
+                    } else if c.id() == chunk_id(b"IART") {
+                        &mut info.artist
+                    } else {
+                        continue;
+                    };
+
+                    *value = read_string(&c.read_contents(&mut self.reader)?);
+                }
+
+                Ok::<_, DecodeError>(info)
+            })
+            .transpose()?;
+
+        Ok(AnimatedCursor {
+            metadata,
+            frames,
+            sequence,
+            rates,
+            info,
+        })
+    }
+}
+
+/// Reads a null-terminated string from the contents of an 'INFO' subchunk.
+///
+/// Returns `None` if the string is empty.
+fn read_string(contents: &[u8]) -> Option<String> {
+    let end = contents
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(contents.len());
+    let value = String::from_utf8_lossy(&contents[..end]).trim().to_string();
+
+    (!value.is_empty()).then_some(value)
+}
+
+/// The size of a `BITMAPINFOHEADER` in bytes.
+const BITMAP_INFO_HEADER_SIZE: u32 = 40;
+
+/// The sizes of `BITMAPINFOHEADER` and its larger versions, up to
+/// `BITMAPV5HEADER`, in bytes.
+const BITMAP_HEADER_SIZES: [u32; 5] = [BITMAP_INFO_HEADER_SIZE, 52, 56, 108, 124];
+
+/// Uncompressed RGB bitmap data.
+const BI_RGB: u32 = 0;
+
+/// Uncompressed bitmap data with color masks. Only supported for 32-bit data,
+/// which is assumed to use the standard BGRA masks.
+const BI_BITFIELDS: u32 = 3;
+
+/// Decodes a raw data frame into a cursor with a single entry.
+///
+/// Raw data frames are device-independent bitmaps. They either start with a
+/// `BITMAPINFOHEADER`, optionally followed by a color table, or contain only
+/// the pixel data, in which case the `width`, `height` and `bit_count` from
+/// the metadata describe it. Pixel rows are stored bottom-up unless the header
+/// has a negative height.
+///
+/// Raw data frames have no hotspot, so the hotspot is set to `(0, 0)`.
+fn decode_raw_frame(
+    contents: &[u8],
+    metadata: &AnimatedCursorMetadata,
+) -> Result<IconDir, DecodeError> {
+    let header_size = contents
+        .get(0..4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .unwrap_or(0);
+
+    // The first pixels of headerless data can look like a header size, so
+    // data that is exactly as long as the metadata says it should be is
+    // always headerless.
+    let headerless_len = (metadata.width as usize * metadata.bit_count as usize).div_ceil(32)
+        * 4
+        * metadata.height as usize;
+    let has_header = (headerless_len == 0 || contents.len() != headerless_len)
+        && BITMAP_HEADER_SIZES.contains(&header_size)
+        && header_size as usize <= contents.len();
+
+    let (width, height, top_down, bit_count, palette, pixels) = if has_header {
+        let mut cursor = std::io::Cursor::new(&contents[4..]);
+        let width = cursor.read_i32::<LittleEndian>()?;
+        let height = cursor.read_i32::<LittleEndian>()?;
+        let _plane_count = cursor.read_u16::<LittleEndian>()?;
+        let bit_count = cursor.read_u16::<LittleEndian>()? as u32;
+        let compression = cursor.read_u32::<LittleEndian>()?;
+        let _image_size = cursor.read_u32::<LittleEndian>()?;
+        let _x_pixels_per_meter = cursor.read_i32::<LittleEndian>()?;
+        let _y_pixels_per_meter = cursor.read_i32::<LittleEndian>()?;
+        let colors_used = cursor.read_u32::<LittleEndian>()?;
+
+        let mut offset = header_size as usize;
+        match compression {
+            BI_RGB => {}
+            // The color masks follow a `BITMAPINFOHEADER`, but are part of the
+            // larger header versions.
+            BI_BITFIELDS if bit_count == 32 => {
+                if header_size == BITMAP_INFO_HEADER_SIZE {
+                    offset += 12;
+                }
+            }
+            _ => return Err(DecodeError::UnsupportedRawDataCompression(compression)),
+        }
+
+        let palette_len = match bit_count {
+            1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+            1 | 4 | 8 => colors_used as usize,
+            _ => 0,
+        };
+        let palette = contents
+            .get(offset..offset + palette_len * 4)
+            .ok_or(DecodeError::InvalidRawDataFrame)?
+            .chunks_exact(4)
+            .map(|bgrx| [bgrx[2], bgrx[1], bgrx[0], u8::MAX])
+            .collect::<Vec<_>>();
+
+        (
+            width.unsigned_abs(),
+            height.unsigned_abs(),
+            height < 0,
+            bit_count,
+            palette,
+            &contents[offset + palette_len * 4..],
+        )
+    } else {
+        (
+            metadata.width,
+            metadata.height,
+            false,
+            metadata.bit_count,
+            Vec::new(),
+            contents,
+        )
+    };
+
+    if !(1..=256).contains(&width) || !(1..=256).contains(&height) {
+        return Err(DecodeError::InvalidRawDataFrame);
+    }
+
+    match bit_count {
+        1 | 4 | 8 if palette.is_empty() => {
+            return Err(DecodeError::UnsupportedRawDataBitCount(bit_count))
+        }
+        1 | 4 | 8 | 24 | 32 => {}
+        _ => return Err(DecodeError::UnsupportedRawDataBitCount(bit_count)),
+    }
+
+    // Rows are padded to a multiple of 4 bytes.
+    let stride = (width as usize * bit_count as usize).div_ceil(32) * 4;
+    if pixels.len() < stride * height as usize {
+        return Err(DecodeError::InvalidRawDataFrame);
+    }
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+
+    for y in 0..height as usize {
+        let src_y = if top_down { y } else { height as usize - 1 - y };
+        let row = &pixels[src_y * stride..(src_y + 1) * stride];
+
+        for x in 0..width as usize {
+            let pixel = match bit_count {
+                32 => [row[x * 4 + 2], row[x * 4 + 1], row[x * 4], row[x * 4 + 3]],
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], u8::MAX],
+                _ => {
+                    let bit = x * bit_count as usize;
+                    let byte = row[bit / 8];
+                    let shift = 8 - bit_count as usize - bit % 8;
+                    let index = (byte >> shift) & ((1 << bit_count) - 1) as u8;
+                    *palette
+                        .get(index as usize)
+                        .ok_or(DecodeError::InvalidRawDataFrame)?
+                }
+            };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+
+    // 32-bit bitmaps without any alpha are fully opaque.
+    if bit_count == 32 && rgba.chunks_exact(4).all(|p| p[3] == 0) {
+        rgba.chunks_exact_mut(4).for_each(|p| p[3] = u8::MAX);
+    }
+
+    let mut image = IconImage::from_rgba_data(width, height, rgba);
+    image.set_cursor_hotspot(Some((0, 0)));
+
+    let mut icon = IconDir::new(ResourceType::Cursor);
+    icon.add_entry(IconDirEntry::encode(&image)?);
+
+    Ok(icon)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::chunk;
+
+    use super::*;
+
+    /// Builds an .ANI file with `frame_count` 1x1 cursor frames, whose header
+    /// has `step_count` steps, followed by a 'seq '-chunk of `sequence`.
+    fn ani_with_sequence(frame_count: u32, step_count: u32, sequence: &[u32]) -> Vec<u8> {
+        let flags =
+            AnimatedCursorFlags::ICON_OR_CURSOR_DATA | AnimatedCursorFlags::HAS_SEQUENCE_CHUNK;
+        let header = [36, frame_count, step_count, 0, 0, 0, 0, 10, flags.bits()]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let mut frames = b"fram".to_vec();
+        for _ in 0..frame_count {
+            let mut icon_dir = IconDir::new(ResourceType::Cursor);
+            let mut image = IconImage::from_rgba_data(1, 1, vec![255; 4]);
+            image.set_cursor_hotspot(Some((0, 0)));
+            icon_dir.add_entry(IconDirEntry::encode(&image).unwrap());
+            let mut icon = Vec::new();
+            icon_dir.write(&mut icon).unwrap();
+            frames.extend(chunk(b"icon", &icon));
+        }
+
+        let sequence = sequence
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let mut contents = b"ACON".to_vec();
+        contents.extend(chunk(b"anih", &header));
+        contents.extend(chunk(b"LIST", &frames));
+        contents.extend(chunk(b"seq ", &sequence));
+
+        chunk(b"RIFF", &contents)
+    }
+
+    /// Builds an .ANI file with a single raw data frame, whose header has the
+    /// given `width`, `height` and `bit_count`.
+    fn ani_with_raw_frame(width: u32, height: u32, bit_count: u32, frame: &[u8]) -> Vec<u8> {
+        let header = [36, 1, 1, width, height, bit_count, 1, 10, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let mut frames = b"fram".to_vec();
+        frames.extend(chunk(b"icon", frame));
+
+        let mut contents = b"ACON".to_vec();
+        contents.extend(chunk(b"anih", &header));
+        contents.extend(chunk(b"LIST", &frames));
+
+        chunk(b"RIFF", &contents)
+    }
+
+    #[test]
+    fn test_decode_sequence() {
+        let bytes = ani_with_sequence(2, 4, &[1, 0, 0, 1]);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert_eq!(cursor.frames.len(), 2);
+        assert_eq!(cursor.sequence, Some(vec![1, 0, 0, 1]));
+        assert_eq!(cursor.step_frame_indices(), vec![1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_decode_sequence_too_short() {
+        let bytes = ani_with_sequence(2, 3, &[1, 0]);
+
+        let result = Decoder::new(std::io::Cursor::new(bytes)).decode();
+
+        assert!(matches!(result, Err(DecodeError::InvalidSequenceLength(8))));
+    }
+
+    #[test]
+    fn test_decode_sequence_invalid_frame_index() {
+        let bytes = ani_with_sequence(2, 2, &[0, 2]);
+
+        let result = Decoder::new(std::io::Cursor::new(bytes)).decode();
+
+        assert!(matches!(
+            result,
+            Err(DecodeError::InvalidSequenceFrameIndex(2))
+        ));
+    }
+
+    #[test]
+    fn test_decode_raw_data_frames() {
+        // A 2x2 BGRA bitmap without a header, stored bottom-up.
+        let pixels = [
+            0, 0, 255, 255, // bottom-left: red
+            0, 255, 0, 255, // bottom-right: green
+            255, 0, 0, 255, // top-left: blue
+            255, 255, 255, 128, // top-right: translucent white
+        ];
+
+        let bytes = ani_with_raw_frame(2, 2, 32, &pixels);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert_eq!(cursor.frames.len(), 1);
+
+        let entry = cursor.frames[0].entries().first().unwrap();
+        assert_eq!(entry.resource_type(), ResourceType::Cursor);
+
+        let icon_image = entry.decode().unwrap();
+        assert_eq!(icon_image.width(), 2);
+        assert_eq!(icon_image.height(), 2);
+        assert_eq!(icon_image.cursor_hotspot(), Some((0, 0)));
+        assert_eq!(
+            icon_image.rgba_data(),
+            &[
+                0, 0, 255, 255, // blue
+                255, 255, 255, 128, // translucent white
+                255, 0, 0, 255, // red
+                0, 255, 0, 255, // green
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_raw_data_frame_with_header() {
+        let mut frame = Vec::new();
+        // A `BITMAPINFOHEADER` of a 2x2 bitmap with 2 palette colors.
+        for value in [40, 2, 2] {
+            frame.extend_from_slice(&u32::to_le_bytes(value));
+        }
+        frame.extend_from_slice(&1u16.to_le_bytes());
+        frame.extend_from_slice(&8u16.to_le_bytes());
+        for value in [BI_RGB, 0, 0, 0, 2, 0] {
+            frame.extend_from_slice(&u32::to_le_bytes(value));
+        }
+        // The BGRX palette.
+        frame.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+        // Rows of palette indices padded to 4 bytes, stored bottom-up.
+        frame.extend_from_slice(&[0, 1, 0, 0, 1, 1, 0, 0]);
+
+        // The metadata doesn't describe frames that have a header.
+        let bytes = ani_with_raw_frame(0, 0, 0, &frame);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        let icon_image = cursor.frames[0].entries()[0].decode().unwrap();
+        assert_eq!(icon_image.width(), 2);
+        assert_eq!(icon_image.height(), 2);
+        assert_eq!(
+            icon_image.rgba_data(),
+            &[
+                0, 0, 255, 255, // blue
+                0, 0, 255, 255, // blue
+                255, 0, 0, 255, // red
+                0, 0, 255, 255, // blue
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_headerless_raw_data_frame_like_header() {
+        // A 4x4 BGRA bitmap without a header, whose first pixel reads as the
+        // size of a `BITMAPINFOHEADER`.
+        let mut pixels = [255; 64];
+        pixels[0..4].copy_from_slice(&BITMAP_INFO_HEADER_SIZE.to_le_bytes());
+
+        let bytes = ani_with_raw_frame(4, 4, 32, &pixels);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        let icon_image = cursor.frames[0].entries()[0].decode().unwrap();
+        assert_eq!(icon_image.width(), 4);
+        assert_eq!(icon_image.height(), 4);
+        // The first pixel is the bottom-left one.
+        assert_eq!(&icon_image.rgba_data()[48..52], &[0, 0, 40, 0]);
+        assert_eq!(&icon_image.rgba_data()[0..4], &[255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_decode_info() {
+        let header = [36, 1, 1, 1, 1, 32, 1, 10, 0]
+            .iter()
+            .flat_map(|v: &u32| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        // Both strings have an odd length, so their chunks are padded.
+        let mut info = b"INFO".to_vec();
+        info.extend(chunk(b"INAM", b"Hand\0"));
+        info.extend(chunk(b"IART", b"Jane Doe\0"));
+
+        let mut frames = b"fram".to_vec();
+        frames.extend(chunk(b"icon", &[0, 0, 255, 255]));
+
+        let mut contents = b"ACON".to_vec();
+        contents.extend(chunk(b"LIST", &info));
+        contents.extend(chunk(b"anih", &header));
+        contents.extend(chunk(b"LIST", &frames));
+
+        let bytes = chunk(b"RIFF", &contents);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert_eq!(
+            cursor.info,
+            Some(AnimatedCursorInfo {
+                title: Some("Hand".to_string()),
+                artist: Some("Jane Doe".to_string()),
+            })
+        );
+        assert_eq!(cursor.frames.len(), 1);
+    }
+
+    #[test]
+    fn test_read_string() {
+        assert_eq!(read_string(b"Hand\0\0"), Some("Hand".to_string()));
+        assert_eq!(read_string(b" Hand \0garbage"), Some("Hand".to_string()));
+        assert_eq!(read_string(b"Hand"), Some("Hand".to_string()));
+        assert_eq!(read_string(b"\0"), None);
+        assert_eq!(read_string(b""), None);
     }
 }
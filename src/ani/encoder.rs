@@ -0,0 +1,266 @@
+use std::{
+    fmt,
+    io::{Error as IoError, Write},
+    time::Duration,
+};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use ico::{IconDir, ResourceType};
+
+use super::{animation::Animation, *};
+
+/// The size of the 'anih'-chunk in bytes.
+const HEADER_SIZE_BYTES: u32 = 36;
+
+#[derive(Debug)]
+pub enum EncodeError {
+    IoError(IoError),
+    NoFrames,
+    NoSteps,
+    InvalidFrameResourceType(ResourceType),
+    InvalidSequenceFrameIndex(usize),
+    InvalidRateLength(usize),
+}
+
+impl std::error::Error for EncodeError {}
+
+impl From<IoError> for EncodeError {
+    fn from(error: IoError) -> Self {
+        EncodeError::IoError(error)
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::IoError(e) => write!(f, "IO error: {}", e),
+            EncodeError::NoFrames => write!(f, "an animated cursor must have at least one frame"),
+            EncodeError::NoSteps => write!(f, "an animated cursor must have at least one step"),
+            EncodeError::InvalidFrameResourceType(resource_type) => {
+                write!(
+                    f,
+                    "frame resource type must be cursor, found: {:?}",
+                    resource_type
+                )
+            }
+            EncodeError::InvalidSequenceFrameIndex(index) => {
+                write!(f, "invalid frame index in sequence: {}", index)
+            }
+            EncodeError::InvalidRateLength(len) => {
+                write!(
+                    f,
+                    "number of rates ({}) must match the number of steps",
+                    len
+                )
+            }
+        }
+    }
+}
+
+/// Converts a [`Duration`] to a number of ticks, where a "tick" equals 1/60th
+/// of a second.
+///
+/// Every step is displayed for at least one tick.
+#[inline(always)]
+fn duration_to_ticks(duration: Duration) -> u32 {
+    ((duration.as_secs_f32() * 60.0).round() as u32).max(1)
+}
+
+impl AnimatedCursor {
+    /// Creates an animated cursor from frames and the animation that plays
+    /// them.
+    ///
+    /// Atlas indices in the animation are used as indices into `frames`. One
+    /// repetition of the animation is stored as the cursor's sequence and
+    /// rates, which are left out when they aren't needed. .ANI files always
+    /// loop, so the animation's repeat behavior is not kept.
+    ///
+    /// Returns an error if there are no frames or the animation has no steps.
+    pub fn from_frames(frames: Vec<IconDir>, animation: &Animation) -> Result<Self, EncodeError> {
+        if frames.is_empty() {
+            return Err(EncodeError::NoFrames);
+        }
+
+        let steps = animation.frames();
+        if steps.is_empty() {
+            return Err(EncodeError::NoSteps);
+        }
+
+        let sequence = steps
+            .iter()
+            .map(|step| {
+                u32::try_from(step.atlas_index)
+                    .ok()
+                    .filter(|_| step.atlas_index < frames.len())
+                    .ok_or(EncodeError::InvalidSequenceFrameIndex(step.atlas_index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rates = steps
+            .iter()
+            .map(|step| duration_to_ticks(step.duration))
+            .collect::<Vec<_>>();
+
+        let ticks_per_frame = rates.first().copied().unwrap_or(1);
+
+        let sequence = (!sequence.iter().copied().eq(0..frames.len() as u32)).then_some(sequence);
+        let rates = rates
+            .iter()
+            .any(|&ticks| ticks != ticks_per_frame)
+            .then_some(rates);
+
+        let mut flags = AnimatedCursorFlags::ICON_OR_CURSOR_DATA;
+        flags.set(AnimatedCursorFlags::HAS_SEQUENCE_CHUNK, sequence.is_some());
+
+        Ok(AnimatedCursor {
+            metadata: AnimatedCursorMetadata {
+                header_size_bytes: HEADER_SIZE_BYTES,
+                frame_count: frames.len() as u32,
+                step_count: sequence.as_ref().map_or(frames.len(), Vec::len) as u32,
+                width: 0,
+                height: 0,
+                bit_count: 0,
+                plane_count: 0,
+                ticks_per_frame,
+                flags,
+            },
+            frames,
+            sequence,
+            rates,
+            info: None,
+        })
+    }
+}
+
+pub struct Encoder<W>
+where
+    W: Write,
+{
+    writer: W,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W) -> Self {
+        Encoder { writer }
+    }
+
+    /// Writes `cursor` as a RIFF 'ACON' file.
+    ///
+    /// Frames are always written as icon or cursor data, so raw data frames
+    /// of decoded files are converted. The frame and step counts and the
+    /// flags of the 'anih'-chunk are derived from the frames and sequence
+    /// rather than taken from the metadata.
+    pub fn encode(&mut self, cursor: &AnimatedCursor) -> Result<(), EncodeError> {
+        if cursor.frames.is_empty() {
+            return Err(EncodeError::NoFrames);
+        }
+
+        let step_count = cursor
+            .sequence
+            .as_ref()
+            .map_or(cursor.frames.len(), Vec::len);
+        if step_count == 0 {
+            return Err(EncodeError::NoSteps);
+        }
+
+        if let Some(index) = cursor
+            .sequence
+            .iter()
+            .flatten()
+            .find(|&&index| index as usize >= cursor.frames.len())
+        {
+            return Err(EncodeError::InvalidSequenceFrameIndex(*index as usize));
+        }
+        if let Some(rates) = cursor.rates.as_ref().filter(|r| r.len() != step_count) {
+            return Err(EncodeError::InvalidRateLength(rates.len()));
+        }
+
+        let mut flags = AnimatedCursorFlags::ICON_OR_CURSOR_DATA;
+        flags.set(
+            AnimatedCursorFlags::HAS_SEQUENCE_CHUNK,
+            cursor.sequence.is_some(),
+        );
+
+        let mut contents = b"ACON".to_vec();
+
+        let mut header = Vec::new();
+        for value in [
+            HEADER_SIZE_BYTES,
+            cursor.frames.len() as u32,
+            step_count as u32,
+            cursor.metadata.width,
+            cursor.metadata.height,
+            cursor.metadata.bit_count,
+            cursor.metadata.plane_count,
+            cursor.metadata.ticks_per_frame,
+            flags.bits(),
+        ] {
+            header.write_u32::<LittleEndian>(value)?;
+        }
+        write_chunk(&mut contents, b"anih", &header)?;
+
+        if let Some(info) = &cursor.info {
+            let mut info_contents = b"INFO".to_vec();
+            for (id, value) in [(b"INAM", &info.title), (b"IART", &info.artist)] {
+                if let Some(value) = value {
+                    let mut string = value.as_bytes().to_vec();
+                    string.push(0);
+                    write_chunk(&mut info_contents, id, &string)?;
+                }
+            }
+            write_chunk(&mut contents, b"LIST", &info_contents)?;
+        }
+
+        if let Some(rates) = &cursor.rates {
+            write_chunk(&mut contents, b"rate", &u32s_to_bytes(rates)?)?;
+        }
+        if let Some(sequence) = &cursor.sequence {
+            write_chunk(&mut contents, b"seq ", &u32s_to_bytes(sequence)?)?;
+        }
+
+        let mut frames_contents = b"fram".to_vec();
+        for frame in &cursor.frames {
+            if frame.resource_type() != ResourceType::Cursor {
+                return Err(EncodeError::InvalidFrameResourceType(frame.resource_type()));
+            }
+
+            let mut icon = Vec::new();
+            frame.write(&mut icon)?;
+            write_chunk(&mut frames_contents, b"icon", &icon)?;
+        }
+        write_chunk(&mut contents, b"LIST", &frames_contents)?;
+
+        write_chunk(&mut self.writer, b"RIFF", &contents)?;
+
+        Ok(())
+    }
+
+    /// Encodes frames and the animation that plays them as an animated cursor.
+    ///
+    /// See [`AnimatedCursor::from_frames`].
+    pub fn encode_frames(
+        &mut self,
+        frames: Vec<IconDir>,
+        animation: &Animation,
+    ) -> Result<(), EncodeError> {
+        self.encode(&AnimatedCursor::from_frames(frames, animation)?)
+    }
+}
+
+/// Writes a RIFF chunk, padding its contents to an even number of bytes.
+fn write_chunk(writer: &mut impl Write, id: &[u8; 4], data: &[u8]) -> Result<(), IoError> {
+    writer.write_all(id)?;
+    writer.write_u32::<LittleEndian>(data.len() as u32)?;
+    writer.write_all(data)?;
+    if data.len() % 2 == 1 {
+        writer.write_u8(0)?;
+    }
+    Ok(())
+}
+
+fn u32s_to_bytes(values: &[u32]) -> Result<Vec<u8>, IoError> {
+    let mut bytes = Vec::with_capacity(values.len() * 4);
+    for &value in values {
+        bytes.write_u32::<LittleEndian>(value)?;
+    }
+    Ok(bytes)
+}
@@ -1,8 +1,11 @@
 pub mod animation;
 pub mod asset;
 pub mod decoder;
+pub mod encoder;
+#[cfg(feature = "packed_asset")]
+pub mod packed_asset;
 #[cfg(feature = "serde_asset")]
-mod serde_asset;
+pub mod serde_asset;
 
 use std::time::Duration;
 
@@ -12,6 +15,8 @@ use ico::IconDir;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use self::animation::Animation;
+
 #[derive(Clone, Debug, Eq, PartialEq, Reflect)]
 #[reflect(Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -64,13 +69,35 @@ bitflags! {
     }
 }
 
+/// Descriptive information about an animated cursor, from the 'LIST'-chunk of
+/// type 'INFO'.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Reflect)]
+#[reflect(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
+pub struct AnimatedCursorInfo {
+    /// The title of the cursor, from the 'INAM'-chunk.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub title: Option<String>,
+    /// The author of the cursor, from the 'IART'-chunk.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub artist: Option<String>,
+}
+
 impl AnimatedCursorMetadata {
     #[inline(always)]
     pub fn duration_per_frame(&self) -> Duration {
-        Duration::from_secs_f32(self.ticks_per_frame as f32 / 60.0)
+        ticks_to_duration(self.ticks_per_frame)
     }
 }
 
+/// Converts a number of ticks, where a "tick" equals 1/60th of a second, to a
+/// [`Duration`].
+#[inline(always)]
+fn ticks_to_duration(ticks: u32) -> Duration {
+    Duration::from_secs_f32(ticks as f32 / 60.0)
+}
+
 #[derive(Clone, Debug, Reflect)]
 #[reflect(Debug)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -79,6 +106,67 @@ pub struct AnimatedCursor {
     pub metadata: AnimatedCursorMetadata,
     #[reflect(ignore)]
     pub frames: Vec<IconDir>,
+    /// The order in which frames are played, from the 'seq '-chunk. Each entry
+    /// is an index into `frames`.
+    ///
+    /// `None` if the file has no 'seq '-chunk, in which case the frames are
+    /// played in order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sequence: Option<Vec<u32>>,
+    /// The number of ticks to display each step for, from the 'rate'-chunk.
+    ///
+    /// `None` if the file has no 'rate'-chunk, in which case every step is
+    /// displayed for `metadata.ticks_per_frame` ticks.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rates: Option<Vec<u32>>,
+    /// The descriptive information, from the 'LIST'-chunk of type 'INFO'.
+    ///
+    /// `None` if the file has no 'INFO' list.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub info: Option<AnimatedCursorInfo>,
+}
+
+impl AnimatedCursor {
+    /// Returns the frame index for each step of the animation.
+    ///
+    /// If the cursor has a sequence, its `step_count` entries are returned.
+    /// Otherwise, every frame is returned in order.
+    pub fn step_frame_indices(&self) -> Vec<usize> {
+        match &self.sequence {
+            Some(sequence) => sequence.iter().map(|&i| i as usize).collect(),
+            None => (0..self.frames.len()).collect(),
+        }
+    }
+
+    /// Returns the duration of each step of the animation.
+    ///
+    /// If the cursor has rates, each step uses its own rate. Otherwise, every
+    /// step uses the duration from the metadata.
+    pub fn step_durations(&self) -> Vec<Duration> {
+        let step_count = self.step_frame_indices().len();
+
+        match &self.rates {
+            Some(rates) => rates
+                .iter()
+                .take(step_count)
+                .map(|&ticks| ticks_to_duration(ticks))
+                .collect(),
+            None => vec![self.metadata.duration_per_frame(); step_count],
+        }
+    }
+
+    /// Returns the [`Animation`] described by the cursor's sequence and rates.
+    ///
+    /// Frame indices are used as atlas indices, so the frames must be added to
+    /// the texture atlas in order.
+    pub fn animation(&self) -> Animation {
+        Animation::from_frame_durations(
+            self.step_frame_indices()
+                .into_iter()
+                .zip(self.step_durations())
+                .map(|(index, duration)| (index, duration.as_millis() as u32)),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -93,7 +181,11 @@ mod tests {
 
     use ico::ResourceType;
 
-    use crate::ani::decoder::Decoder;
+    use crate::ani::{
+        animation::{Animation, AnimationDirection, AnimationRepeat},
+        decoder::Decoder,
+        encoder::{EncodeError, Encoder},
+    };
 
     use super::*;
 
@@ -257,6 +349,116 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_encode_round_trip() {
+        let frames = [(255, (1, 2)), (128, (3, 4))]
+            .iter()
+            .map(|&(alpha, hotspot)| {
+                let image = image::RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, alpha]));
+                crate::cur::StaticCursor::from_images([(&image, hotspot)])
+                    .unwrap()
+                    .0
+            })
+            .collect::<Vec<_>>();
+
+        let animation = Animation::from_frame_durations([(0, 50), (1, 50), (0, 200)]);
+
+        let mut cursor = AnimatedCursor::from_frames(frames, &animation).unwrap();
+        cursor.info = Some(AnimatedCursorInfo {
+            title: Some("Pointer".to_string()),
+            artist: None,
+        });
+
+        let mut bytes = Vec::new();
+        Encoder::new(&mut bytes).encode(&cursor).unwrap();
+
+        let decoded = Decoder::new(std::io::Cursor::new(bytes.clone()))
+            .decode()
+            .unwrap();
+
+        assert_eq!(decoded.metadata, cursor.metadata);
+        assert_eq!(decoded.sequence, Some(vec![0, 1, 0]));
+        assert_eq!(decoded.rates, Some(vec![3, 3, 12]));
+        assert_eq!(decoded.info, cursor.info);
+        assert_eq!(decoded.animation(), animation);
+
+        for (frame, hotspot) in decoded.frames.iter().zip([(1, 2), (3, 4)]) {
+            let entry = frame.entries().first().unwrap();
+            assert_eq!(entry.resource_type(), ResourceType::Cursor);
+            assert_eq!(entry.decode().unwrap().cursor_hotspot(), Some(hotspot));
+        }
+
+        // Re-encoding the decoded cursor gives the same bytes.
+        let mut reencoded = Vec::new();
+        Encoder::new(&mut reencoded).encode(&decoded).unwrap();
+        assert_eq!(reencoded, bytes);
+    }
+
+    #[test]
+    fn test_encode_empty() {
+        let image = image::RgbaImage::new(8, 8);
+        let frame = crate::cur::StaticCursor::from_images([(&image, (0, 0))])
+            .unwrap()
+            .0;
+        let empty = Animation {
+            clips: Vec::new(),
+            repeat: AnimationRepeat::Loop,
+            direction: AnimationDirection::Forwards,
+        };
+
+        assert!(matches!(
+            AnimatedCursor::from_frames(Vec::new(), &Animation::from_frame_durations([(0, 50)])),
+            Err(EncodeError::NoFrames)
+        ));
+        assert!(matches!(
+            AnimatedCursor::from_frames(vec![frame.clone()], &empty),
+            Err(EncodeError::NoSteps)
+        ));
+
+        let mut cursor =
+            AnimatedCursor::from_frames(vec![frame], &Animation::from_frame_durations([(0, 50)]))
+                .unwrap();
+        cursor.sequence = Some(Vec::new());
+        assert!(matches!(
+            Encoder::new(Vec::new()).encode(&cursor),
+            Err(EncodeError::NoSteps)
+        ));
+    }
+
+    #[test]
+    fn test_encode_invalid_sequence() {
+        let image = image::RgbaImage::new(8, 8);
+        let frame = crate::cur::StaticCursor::from_images([(&image, (0, 0))])
+            .unwrap()
+            .0;
+
+        assert!(matches!(
+            AnimatedCursor::from_frames(
+                vec![frame.clone()],
+                &Animation::from_frame_durations([(0, 50), (1, 50)])
+            ),
+            Err(EncodeError::InvalidSequenceFrameIndex(1))
+        ));
+        // Indices that don't fit in a sequence chunk are reported as they are.
+        let index = usize::MAX;
+        assert!(matches!(
+            AnimatedCursor::from_frames(
+                vec![frame.clone()],
+                &Animation::from_frame_durations([(index, 50)])
+            ),
+            Err(EncodeError::InvalidSequenceFrameIndex(i)) if i == index
+        ));
+
+        let mut cursor =
+            AnimatedCursor::from_frames(vec![frame], &Animation::from_frame_durations([(0, 50)]))
+                .unwrap();
+        cursor.sequence = Some(vec![0, 5]);
+        assert!(matches!(
+            Encoder::new(Vec::new()).encode(&cursor),
+            Err(EncodeError::InvalidSequenceFrameIndex(5))
+        ));
+    }
+
     #[cfg(feature = "serde")]
     fn append_ext(ext: impl AsRef<OsStr>, path: PathBuf) -> PathBuf {
         let mut os_string: OsString = path.into();
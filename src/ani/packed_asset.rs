@@ -0,0 +1,465 @@
+use std::{
+    any::type_name,
+    io::{self, Cursor, Read, Write},
+    marker::PhantomData,
+};
+
+use bevy_asset::{
+    io::{Reader, Writer},
+    processor::{Process, ProcessContext, ProcessError},
+    saver::{AssetSaver, SavedAsset},
+    AssetLoader, AssetPath, AsyncWriteExt, LoadContext, RenderAssetUsages,
+};
+use bevy_image::{Image, IntoDynamicImageError, TextureAtlasLayout};
+use bevy_reflect::prelude::*;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::packed::{
+    read_header, read_sheet, read_string, write_header, write_sheet, write_string, PackedKind,
+    PackedSheet,
+};
+
+use super::{
+    animation::{
+        Animation, AnimationClip, AnimationDirection, AnimationDuration, AnimationMarker,
+        AnimationRepeat,
+    },
+    asset::AnimatedCursor,
+    serde_asset::{Deserializer, SerdeAnimatedCursorLoader, SerdeAnimatedCursorLoaderSettings},
+};
+
+/// Saves animated cursors in the packed binary format.
+///
+/// The cursor's image must be an `image` labeled asset, e.g. one loaded by
+/// [`SerdeAnimatedCursorLoader`] with `embed_image` set, so that its pixels
+/// are available. The metadata and descriptive information of cursors loaded
+/// from .ANI files are not saved.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct PackedAnimatedCursorSaver;
+
+/// Possible errors that can be produced by [`PackedAnimatedCursorSaver`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum PackedAnimatedCursorSaverError {
+    /// An [IO](std::io) error.
+    #[error("could not save asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("missing labeled asset: {0}")]
+    MissingLabeledAsset(&'static str),
+    /// An [IntoDynamicImageError] error.
+    #[error("could not convert image: {0}")]
+    IntoDynamicImageError(#[from] IntoDynamicImageError),
+}
+
+impl AssetSaver for PackedAnimatedCursorSaver {
+    type Asset = AnimatedCursor;
+    type Settings = ();
+    type OutputLoader = PackedAnimatedCursorLoader;
+    type Error = PackedAnimatedCursorSaverError;
+
+    async fn save(
+        &self,
+        writer: &mut Writer,
+        asset: SavedAsset<'_, '_, Self::Asset>,
+        _settings: &Self::Settings,
+        _asset_path: AssetPath<'_>,
+    ) -> Result<PackedAnimatedCursorLoaderSettings, Self::Error> {
+        let image = asset
+            .get_labeled::<Image>("image")
+            .ok_or(PackedAnimatedCursorSaverError::MissingLabeledAsset("image"))?;
+        let texture_atlas_layout = asset
+            .get_labeled::<TextureAtlasLayout>("texture_atlas_layout")
+            .ok_or(PackedAnimatedCursorSaverError::MissingLabeledAsset(
+                "texture_atlas_layout",
+            ))?;
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, PackedKind::Animated)?;
+        write_sheet(
+            &mut bytes,
+            &PackedSheet {
+                image: image.get().clone().try_into_dynamic()?,
+                texture_atlas_layout: texture_atlas_layout.get().clone(),
+                hotspots: asset.hotspots.clone(),
+                variants: asset.variants.clone(),
+            },
+        )?;
+
+        write_animation(&mut bytes, &asset.animation)?;
+
+        bytes.write_u32::<LittleEndian>(asset.animations.len() as u32)?;
+        for (name, animation) in &asset.animations {
+            write_string(&mut bytes, name)?;
+            write_animation(&mut bytes, animation)?;
+        }
+
+        writer.write_all(&bytes).await?;
+
+        Ok(PackedAnimatedCursorLoaderSettings::default())
+    }
+}
+
+/// A loader for animated cursor assets in the packed binary format.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct PackedAnimatedCursorLoader;
+
+/// Settings for loading animated cursors with [`PackedAnimatedCursorLoader`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PackedAnimatedCursorLoaderSettings {
+    /// The usage of the image.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+}
+
+/// Possible errors that can be produced by [`PackedAnimatedCursorLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum PackedAnimatedCursorLoaderError {
+    /// An [IO](std::io) error.
+    #[error("could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl AssetLoader for PackedAnimatedCursorLoader {
+    type Asset = AnimatedCursor;
+    type Settings = PackedAnimatedCursorLoaderSettings;
+    type Error = PackedAnimatedCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let mut reader = Cursor::new(bytes);
+        read_header(&mut reader, PackedKind::Animated)?;
+        let sheet = read_sheet(&mut reader)?;
+
+        let animation = read_animation(&mut reader)?;
+        let animations = (0..reader.read_u32::<LittleEndian>()?)
+            .map(|_| Ok((read_string(&mut reader)?, read_animation(&mut reader)?)))
+            .collect::<io::Result<_>>()?;
+
+        let image = Image::from_dynamic(sheet.image, true, settings.asset_usage);
+
+        let image = load_context.add_labeled_asset("image".to_string(), image);
+        let texture_atlas_layout = load_context.add_labeled_asset(
+            "texture_atlas_layout".to_string(),
+            sheet.texture_atlas_layout,
+        );
+
+        Ok(AnimatedCursor {
+            metadata: None,
+            info: None,
+            image,
+            texture_atlas_layout,
+            hotspots: sheet.hotspots,
+            variants: sheet.variants,
+            animation,
+            animations,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ani.packed"]
+    }
+}
+
+/// Processes text animated cursors into the packed binary format.
+///
+/// The cursor is loaded with [`SerdeAnimatedCursorLoader`], so its image's
+/// color key and flips are applied and its atlas is laid out once, when
+/// processing, and then saved with [`PackedAnimatedCursorSaver`].
+#[derive(TypePath)]
+pub struct PackedAnimatedCursorProcessor<D: Deserializer + TypePath> {
+    _phantom: PhantomData<D>,
+}
+
+impl<D: Deserializer + TypePath> Default for PackedAnimatedCursorProcessor<D> {
+    fn default() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<D: Deserializer + TypePath> Process for PackedAnimatedCursorProcessor<D> {
+    type Settings = ();
+    type OutputLoader = PackedAnimatedCursorLoader;
+
+    async fn process(
+        &self,
+        context: &mut ProcessContext<'_>,
+        _settings: &Self::Settings,
+        writer: &mut Writer,
+    ) -> Result<PackedAnimatedCursorLoaderSettings, ProcessError> {
+        let loaded = context
+            .load_source_asset::<SerdeAnimatedCursorLoader<D>>(&SerdeAnimatedCursorLoaderSettings {
+                embed_image: true,
+            })
+            .await?;
+
+        // The source asset was loaded by an animated cursor loader, so it is
+        // always an animated cursor.
+        let asset = SavedAsset::<AnimatedCursor>::from_loaded(&loaded).ok_or_else(|| {
+            ProcessError::AssetTransformError(
+                format!(
+                    "expected a source asset of type {}",
+                    type_name::<AnimatedCursor>()
+                )
+                .into(),
+            )
+        })?;
+
+        PackedAnimatedCursorSaver
+            .save(writer, asset, &(), context.path().clone())
+            .await
+            .map_err(|error| ProcessError::AssetSaveError(error.into()))
+    }
+}
+
+fn write_repeat(writer: &mut impl Write, repeat: Option<usize>) -> io::Result<()> {
+    writer.write_u8(repeat.is_some() as u8)?;
+    writer.write_u32::<LittleEndian>(repeat.unwrap_or_default() as u32)
+}
+
+fn read_repeat(reader: &mut impl Read) -> io::Result<Option<usize>> {
+    let is_some = reader.read_u8()? != 0;
+    let repeat = reader.read_u32::<LittleEndian>()? as usize;
+    Ok(is_some.then_some(repeat))
+}
+
+fn write_direction(writer: &mut impl Write, direction: AnimationDirection) -> io::Result<()> {
+    writer.write_u8(match direction {
+        AnimationDirection::Forwards => 0,
+        AnimationDirection::Backwards => 1,
+        AnimationDirection::PingPong => 2,
+    })
+}
+
+fn read_direction(reader: &mut impl Read) -> io::Result<AnimationDirection> {
+    match reader.read_u8()? {
+        0 => Ok(AnimationDirection::Forwards),
+        1 => Ok(AnimationDirection::Backwards),
+        2 => Ok(AnimationDirection::PingPong),
+        value => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid animation direction: {}", value),
+        )),
+    }
+}
+
+/// Writes an animation. A repeat of [`AnimationRepeat::Loop`] is written as no
+/// repeat count.
+fn write_animation(writer: &mut impl Write, animation: &Animation) -> io::Result<()> {
+    write_repeat(
+        writer,
+        match animation.repeat {
+            AnimationRepeat::Loop => None,
+            AnimationRepeat::Times(times) => Some(times),
+        },
+    )?;
+    write_direction(writer, animation.direction)?;
+
+    writer.write_u32::<LittleEndian>(animation.clips.len() as u32)?;
+    for clip in &animation.clips {
+        writer.write_u32::<LittleEndian>(clip.atlas_indices.len() as u32)?;
+        for &atlas_index in &clip.atlas_indices {
+            writer.write_u32::<LittleEndian>(atlas_index as u32)?;
+        }
+
+        let (kind, millis) = match clip.duration {
+            AnimationDuration::PerFrame(millis) => (0, millis),
+            AnimationDuration::PerRepetition(millis) => (1, millis),
+        };
+        writer.write_u8(kind)?;
+        writer.write_u32::<LittleEndian>(millis)?;
+
+        write_direction(writer, clip.direction)?;
+        write_repeat(writer, clip.repeat)?;
+
+        writer.write_u32::<LittleEndian>(clip.markers.len() as u32)?;
+        for marker in &clip.markers {
+            write_string(writer, &marker.name)?;
+            writer.write_u32::<LittleEndian>(marker.frame as u32)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_animation(reader: &mut impl Read) -> io::Result<Animation> {
+    let repeat = match read_repeat(reader)? {
+        None => AnimationRepeat::Loop,
+        Some(times) => AnimationRepeat::Times(times),
+    };
+    let direction = read_direction(reader)?;
+
+    let clips = (0..reader.read_u32::<LittleEndian>()?)
+        .map(|_| {
+            let atlas_indices = (0..reader.read_u32::<LittleEndian>()?)
+                .map(|_| Ok(reader.read_u32::<LittleEndian>()? as usize))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            let kind = reader.read_u8()?;
+            let millis = reader.read_u32::<LittleEndian>()?;
+            let duration = match kind {
+                0 => AnimationDuration::PerFrame(millis),
+                1 => AnimationDuration::PerRepetition(millis),
+                value => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid animation duration: {}", value),
+                    ))
+                }
+            };
+
+            Ok(AnimationClip {
+                atlas_indices,
+                duration,
+                direction: read_direction(reader)?,
+                repeat: read_repeat(reader)?,
+                markers: (0..reader.read_u32::<LittleEndian>()?)
+                    .map(|_| {
+                        Ok(AnimationMarker {
+                            name: read_string(reader)?,
+                            frame: reader.read_u32::<LittleEndian>()? as usize,
+                        })
+                    })
+                    .collect::<io::Result<Vec<_>>>()?,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok(Animation {
+        clips,
+        repeat,
+        direction,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bevy_asset::prelude::*;
+
+    use crate::{
+        ani::asset::AnimatedCursorLoaderSettings,
+        testing::{ani_bytes, create_app, load_with_settings, save_with_labeled_atlas},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_animation_round_trip() {
+        let animation = Animation {
+            clips: vec![
+                AnimationClip {
+                    atlas_indices: vec![3, 0, 1, 2],
+                    duration: AnimationDuration::PerFrame(75),
+                    direction: AnimationDirection::PingPong,
+                    repeat: Some(3),
+                    markers: vec![
+                        AnimationMarker {
+                            name: "flip".to_string(),
+                            frame: 1,
+                        },
+                        AnimationMarker {
+                            name: "land".to_string(),
+                            frame: 3,
+                        },
+                    ],
+                },
+                AnimationClip {
+                    atlas_indices: vec![2],
+                    duration: AnimationDuration::PerRepetition(5000),
+                    direction: AnimationDirection::Forwards,
+                    repeat: None,
+                    markers: Vec::new(),
+                },
+            ],
+            repeat: AnimationRepeat::Times(2),
+            direction: AnimationDirection::Backwards,
+        };
+
+        let mut bytes = Vec::new();
+        write_animation(&mut bytes, &animation).unwrap();
+
+        assert_eq!(read_animation(&mut Cursor::new(bytes)).unwrap(), animation);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(3, &[16, 32], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |_| {},
+        )
+        .unwrap();
+        let mut cursor = app
+            .world()
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap()
+            .clone();
+        cursor.animations.insert(
+            "busy".to_string(),
+            Animation::from_frame_durations([(2, 50), (1, 50)]),
+        );
+
+        let bytes = save_with_labeled_atlas(
+            &app,
+            &PackedAnimatedCursorSaver,
+            &cursor,
+            &cursor.image,
+            &cursor.texture_atlas_layout,
+        )
+        .unwrap();
+        dir.insert_asset(Path::new("cursor.ani.packed"), bytes);
+
+        let handle = load_with_settings::<AnimatedCursor, PackedAnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani.packed",
+            |_| {},
+        )
+        .unwrap();
+        let world = app.world();
+        let packed = world
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+
+        assert_eq!(packed.animation, cursor.animation);
+        assert_eq!(packed.animations, cursor.animations);
+        assert_eq!(packed.variants, cursor.variants);
+        for index in 0..6 {
+            assert_eq!(
+                packed.hotspot_or_default(index),
+                cursor.hotspot_or_default(index)
+            );
+        }
+
+        let layouts = world.resource::<Assets<TextureAtlasLayout>>();
+        assert_eq!(
+            layouts.get(&packed.texture_atlas_layout),
+            layouts.get(&cursor.texture_atlas_layout)
+        );
+        let images = world.resource::<Assets<Image>>();
+        assert_eq!(
+            images.get(&packed.image).unwrap().data,
+            images.get(&cursor.image).unwrap().data
+        );
+    }
+}
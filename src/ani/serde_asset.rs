@@ -1,21 +1,34 @@
 #[cfg(feature = "serde_toml_asset")]
 use std::str::from_utf8;
-use std::{fmt::Debug, marker::PhantomData};
+use std::{collections::BTreeMap, fmt::Debug, marker::PhantomData};
 
 use bevy_app::prelude::*;
 use bevy_asset::{io::Reader, prelude::*, AssetLoader, LoadContext};
 use bevy_image::TextureAtlasLayout;
-use bevy_math::UVec2;
 use bevy_reflect::prelude::*;
+use image::{DynamicImage, RgbaImage};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     asset_image::{load_image, LoadImageError},
+    atlas_grid::{hotspots_from_tiles, pack_tiles, process_sheet},
+    cur::{
+        decoder::decode_entry, encoder::EncodeError as CurEncodeError,
+        StaticCursor as DecodedStaticCursor,
+    },
     hotspot::CursorHotspots,
+    variant::{best_size_index, DEFAULT_CURSOR_SIZE},
 };
 
-use super::{animation::Animation, asset::AnimatedCursor};
+#[cfg(feature = "packed_asset")]
+use super::packed_asset::PackedAnimatedCursorProcessor;
+use super::{
+    animation::Animation, asset::AnimatedCursor, encoder::EncodeError,
+    AnimatedCursor as DecodedAnimatedCursor,
+};
+
+pub use crate::cur::serde_asset::SerdeTextureAtlasLayout;
 
 /// A plugin for loading animated cursor assets using Serde.
 pub struct SerdeAnimatedCursorAssetPlugin<D: Deserializer> {
@@ -40,6 +53,16 @@ impl<D: Deserializer + TypePath> Plugin for SerdeAnimatedCursorAssetPlugin<D> {
             D::default(),
             self.extensions.clone(),
         ));
+
+        // Pack the cursors when processing assets, so that release builds load
+        // them without any further image processing.
+        #[cfg(feature = "packed_asset")]
+        {
+            app.register_asset_processor(PackedAnimatedCursorProcessor::<D>::default());
+            for extension in &self.extensions {
+                app.set_default_asset_processor::<PackedAnimatedCursorProcessor<D>>(extension);
+            }
+        }
     }
 }
 
@@ -53,8 +76,11 @@ pub struct SerdeAnimatedCursor {
     /// The hotspot data.
     #[serde(default)]
     pub hotspots: CursorHotspots,
-    /// The animation to play.
+    /// The animation to play by default.
     pub animation: Animation,
+    /// Additional animations that can be switched to by name.
+    #[serde(default)]
+    pub animations: BTreeMap<String, Animation>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Reflect, Serialize)]
@@ -74,19 +100,122 @@ pub struct SerdeImage {
     pub flip_y: bool,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Reflect, Serialize)]
-#[reflect(Debug, Default, Deserialize, Serialize)]
-pub struct SerdeTextureAtlasLayout {
-    /// The size of each tile, in pixels.
-    pub tile_size: UVec2,
-    /// The number columns on the sprite sheet.
-    pub columns: u32,
-    /// The number of rows on the sprite sheet.
-    pub rows: u32,
-    /// The padding between each tile, in pixels.
-    pub padding: Option<UVec2>,
-    /// The global offset of the grid, in pixels.
-    pub offset: Option<UVec2>,
+/// Possible errors that can be produced when converting between a
+/// [`SerdeAnimatedCursor`] and an .ANI file.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SerdeAnimatedCursorConvertError {
+    /// An [IO](std::io) error.
+    #[error("could not decode cursor image: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [CurEncodeError] error.
+    #[error("could not encode frame: {0}")]
+    CurEncodeError(#[from] CurEncodeError),
+    /// An [EncodeError] error.
+    #[error("could not encode animated cursor: {0}")]
+    EncodeError(#[from] EncodeError),
+    #[error("tile {0} is outside of the sprite sheet")]
+    TileOutOfBounds(usize),
+    #[error("frame {0} has no images")]
+    EmptyFrame(usize),
+}
+
+impl SerdeAnimatedCursor {
+    /// Converts the cursor's default animation to an .ANI file.
+    ///
+    /// `image` is the sprite sheet at `self.image.path`. Its color key and
+    /// flips are applied before it is sliced by the texture atlas layout.
+    /// Only the tiles used by the animation become frames. Named animations
+    /// are not converted because .ANI files have a single animation.
+    pub fn to_ani(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<DecodedAnimatedCursor, SerdeAnimatedCursorConvertError> {
+        let sheet = process_sheet(
+            image,
+            self.image.color_key,
+            self.image.flip_x,
+            self.image.flip_y,
+        );
+        let grid = self.texture_atlas_layout.grid();
+
+        let mut atlas_indices = self
+            .animation
+            .clips
+            .iter()
+            .flat_map(|clip| clip.atlas_indices.iter().copied())
+            .collect::<Vec<_>>();
+        atlas_indices.sort_unstable();
+        atlas_indices.dedup();
+
+        let frames = atlas_indices
+            .iter()
+            .map(|&i| {
+                let tile = grid
+                    .tile(&sheet, i)
+                    .ok_or(SerdeAnimatedCursorConvertError::TileOutOfBounds(i))?;
+                let frame =
+                    DecodedStaticCursor::from_images([(&tile, self.hotspots.get_or_default(i))])?;
+                Ok(frame.0)
+            })
+            .collect::<Result<Vec<_>, SerdeAnimatedCursorConvertError>>()?;
+
+        // Frames are stored in atlas index order, so map each atlas index to
+        // the index of its frame.
+        let mut animation = self.animation.clone();
+        for clip in &mut animation.clips {
+            for atlas_index in &mut clip.atlas_indices {
+                *atlas_index = atlas_indices
+                    .binary_search(atlas_index)
+                    .map_err(|_| SerdeAnimatedCursorConvertError::TileOutOfBounds(*atlas_index))?;
+            }
+        }
+
+        Ok(DecodedAnimatedCursor::from_frames(frames, &animation)?)
+    }
+
+    /// Converts an .ANI file to a text cursor and its sprite sheet.
+    ///
+    /// Each frame becomes a tile in a single row, in order, using the entry
+    /// whose size best matches [`DEFAULT_CURSOR_SIZE`]. The returned cursor
+    /// refers to the sprite sheet by `image_path`, so save the sprite sheet
+    /// there, e.g. as a PNG.
+    pub fn from_ani(
+        cursor: &DecodedAnimatedCursor,
+        image_path: impl Into<String>,
+    ) -> Result<(Self, RgbaImage), SerdeAnimatedCursorConvertError> {
+        let (tiles, hotspots): (Vec<_>, Vec<_>) = cursor
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let entry = best_size_index(
+                    frame.entries().iter().map(|e| e.width().max(e.height())),
+                    DEFAULT_CURSOR_SIZE,
+                )
+                .ok_or(SerdeAnimatedCursorConvertError::EmptyFrame(i))?;
+                Ok(decode_entry(&frame.entries()[entry])?)
+            })
+            .collect::<Result<Vec<_>, SerdeAnimatedCursorConvertError>>()?
+            .into_iter()
+            .unzip();
+
+        let (sheet, grid) = pack_tiles(&tiles);
+
+        Ok((
+            Self {
+                image: SerdeImage {
+                    path: image_path.into(),
+                    ..Default::default()
+                },
+                texture_atlas_layout: SerdeTextureAtlasLayout::from_grid(&grid),
+                hotspots: hotspots_from_tiles(&hotspots),
+                animation: cursor.animation(),
+                animations: BTreeMap::new(),
+            },
+            sheet,
+        ))
+    }
 }
 
 /// Possible errors that can be produced by deserialization.
@@ -162,6 +291,18 @@ pub struct SerdeAnimatedCursorLoader<D: Deserializer + TypePath> {
     deserializer: D,
 }
 
+/// Settings for loading animated cursors with [`SerdeAnimatedCursorLoader`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SerdeAnimatedCursorLoaderSettings {
+    /// Whether to always load the image as an `image` labeled asset, even if
+    /// it has no color key and isn't flipped.
+    ///
+    /// This makes the image's pixels available to asset savers, e.g. when
+    /// packing the cursor during asset processing.
+    pub embed_image: bool,
+}
+
 /// Possible errors that can be produced by [`SerdeAnimatedCursorLoader`].
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -179,12 +320,12 @@ pub enum SerdeAnimatedCursorLoaderError {
 
 impl<D: Deserializer + TypePath> AssetLoader for SerdeAnimatedCursorLoader<D> {
     type Asset = AnimatedCursor;
-    type Settings = ();
+    type Settings = SerdeAnimatedCursorLoaderSettings;
     type Error = SerdeAnimatedCursorLoaderError;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
@@ -192,10 +333,14 @@ impl<D: Deserializer + TypePath> AssetLoader for SerdeAnimatedCursorLoader<D> {
 
         let c = self.deserializer.deserialize(&bytes)?;
 
-        // Load the image asset. If the image has a color key or needs to be
-        // flipped, load it as a dynamic image so we can process it now.
-        // Otherwise, load it as a regular asset.
-        let image = if c.image.color_key.is_some() || c.image.flip_x || c.image.flip_y {
+        // Load the image asset. If the image has a color key, needs to be
+        // flipped or should be embedded, load it as a dynamic image so we can
+        // process it now. Otherwise, load it as a regular asset.
+        let image = if settings.embed_image
+            || c.image.color_key.is_some()
+            || c.image.flip_x
+            || c.image.flip_y
+        {
             let image = load_image(
                 load_context,
                 &c.image.path,
@@ -226,10 +371,13 @@ impl<D: Deserializer + TypePath> AssetLoader for SerdeAnimatedCursorLoader<D> {
 
         Ok(AnimatedCursor {
             metadata: None,
+            info: None,
             image,
             texture_atlas_layout,
             hotspots: c.hotspots,
+            variants: Vec::new(),
             animation: c.animation,
+            animations: c.animations,
         })
     }
 
@@ -247,3 +395,199 @@ impl<D: Deserializer + TypePath> SerdeAnimatedCursorLoader<D> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bevy_math::UVec2;
+
+    use crate::ani::animation::{AnimationClip, AnimationDuration};
+
+    use super::*;
+
+    #[test]
+    fn test_ani_round_trip() {
+        let sheet = RgbaImage::from_fn(24, 8, |x, _| image::Rgba([(x / 8) as u8 * 100, 0, 0, 255]));
+
+        let cursor = SerdeAnimatedCursor {
+            image: SerdeImage {
+                path: "sheet.png".to_string(),
+                ..Default::default()
+            },
+            texture_atlas_layout: SerdeTextureAtlasLayout {
+                tile_size: UVec2::splat(8),
+                columns: 3,
+                rows: 1,
+                padding: None,
+                offset: None,
+            },
+            hotspots: CursorHotspots {
+                default: (1, 1),
+                overrides: HashMap::from([(2, (4, 5))]),
+            },
+            animation: Animation {
+                clips: vec![AnimationClip {
+                    atlas_indices: vec![2, 1],
+                    duration: AnimationDuration::PerFrame(100),
+                    direction: Default::default(),
+                    repeat: None,
+                    markers: Vec::new(),
+                }],
+                repeat: Default::default(),
+                direction: Default::default(),
+            },
+            animations: BTreeMap::new(),
+        };
+
+        let ani = cursor.to_ani(&DynamicImage::ImageRgba8(sheet)).unwrap();
+
+        // Only the two tiles used by the animation become frames.
+        assert_eq!(ani.frames.len(), 2);
+        assert_eq!(ani.sequence, Some(vec![1, 0]));
+
+        let (converted, sheet) = SerdeAnimatedCursor::from_ani(&ani, "converted.png").unwrap();
+
+        assert_eq!(converted.image.path, "converted.png");
+        assert_eq!(converted.texture_atlas_layout.tile_size, UVec2::splat(8));
+        assert_eq!(converted.texture_atlas_layout.columns, 2);
+        assert_eq!((sheet.width(), sheet.height()), (16, 8));
+        assert_eq!(sheet.get_pixel(0, 0).0, [100, 0, 0, 255]);
+        assert_eq!(sheet.get_pixel(8, 0).0, [200, 0, 0, 255]);
+        assert_eq!(converted.hotspots.get_or_default(0), (1, 1));
+        assert_eq!(converted.hotspots.get_or_default(1), (4, 5));
+        assert_eq!(converted.animation.clips[0].atlas_indices, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_from_ani_empty_frame() {
+        let image = RgbaImage::new(8, 8);
+        let frame = DecodedStaticCursor::from_images([(&image, (0, 0))])
+            .unwrap()
+            .0;
+        let mut ani = DecodedAnimatedCursor::from_frames(
+            vec![frame],
+            &Animation::from_frame_durations([(0, 100)]),
+        )
+        .unwrap();
+        ani.frames
+            .push(ico::IconDir::new(ico::ResourceType::Cursor));
+
+        // Frames without images are not silently dropped.
+        assert!(matches!(
+            SerdeAnimatedCursor::from_ani(&ani, "converted.png"),
+            Err(SerdeAnimatedCursorConvertError::EmptyFrame(1))
+        ));
+    }
+
+    /// Asserts that the clips of `cursor` repeat 3 times and once, that its
+    /// first clip has a marker and that it has a "busy" animation.
+    #[cfg(any(
+        feature = "serde_json_asset",
+        feature = "serde_ron_asset",
+        feature = "serde_toml_asset"
+    ))]
+    fn assert_deserialized(cursor: &SerdeAnimatedCursor) {
+        use crate::ani::animation::{AnimationMarker, AnimationRepeat};
+
+        let clips = &cursor.animation.clips;
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips[0].atlas_indices, vec![3, 0, 1, 2]);
+        assert_eq!(clips[0].duration, AnimationDuration::PerFrame(75));
+        assert_eq!(clips[0].repeat, Some(3));
+        assert_eq!(clips[1].repeat, None);
+        assert_eq!(
+            clips[0].markers,
+            vec![AnimationMarker {
+                name: "flip".to_string(),
+                frame: 2,
+            }]
+        );
+
+        assert_eq!(cursor.animations.len(), 1);
+        let busy = &cursor.animations["busy"];
+        assert_eq!(busy.repeat, AnimationRepeat::Times(3));
+        assert_eq!(busy.clips[0].atlas_indices, vec![4, 5]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json_asset")]
+    fn test_deserialize_json() {
+        let json = r#"{
+            "image": { "path": "sheet.png" },
+            "texture_atlas_layout": { "tile_size": [32, 32], "columns": 2, "rows": 2 },
+            "animation": {
+                "clips": [
+                    {
+                        "atlas_indices": [3, 0, 1, 2],
+                        "duration": { "PerFrame": 75 },
+                        "repeat": 3,
+                        "markers": [{ "name": "flip", "frame": 2 }]
+                    },
+                    { "atlas_indices": [2], "duration": { "PerFrame": 5000 } }
+                ]
+            },
+            "animations": {
+                "busy": {
+                    "repeat": { "Times": 3 },
+                    "clips": [{ "atlas_indices": [4, 5], "duration": { "PerFrame": 50 } }]
+                }
+            }
+        }"#;
+
+        assert_deserialized(&JsonDeserializer.deserialize(json.as_bytes()).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "serde_ron_asset")]
+    fn test_deserialize_ron() {
+        let ron = r#"(
+            image: (path: "sheet.png"),
+            texture_atlas_layout: (tile_size: (32, 32), columns: 2, rows: 2),
+            animation: (
+                clips: [
+                    (
+                        atlas_indices: [3, 0, 1, 2],
+                        duration: PerFrame(75),
+                        repeat: Some(3),
+                        markers: [(name: "flip", frame: 2)],
+                    ),
+                    (atlas_indices: [2], duration: PerFrame(5000)),
+                ],
+            ),
+            animations: {
+                "busy": (
+                    repeat: Times(3),
+                    clips: [(atlas_indices: [4, 5], duration: PerFrame(50))],
+                ),
+            },
+        )"#;
+
+        assert_deserialized(&RonDeserializer.deserialize(ron.as_bytes()).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "serde_toml_asset")]
+    fn test_deserialize_toml() {
+        let toml = r#"
+            image = { path = "sheet.png" }
+            texture_atlas_layout = { tile_size = [32, 32], columns = 2, rows = 2 }
+
+            [[animation.clips]]
+            atlas_indices = [3, 0, 1, 2]
+            duration = { PerFrame = 75 }
+            repeat = 3
+            markers = [{ name = "flip", frame = 2 }]
+
+            [[animation.clips]]
+            atlas_indices = [2]
+            duration = { PerFrame = 5000 }
+
+            [animations.busy]
+            repeat = { Times = 3 }
+            clips = [{ atlas_indices = [4, 5], duration = { PerFrame = 50 } }]
+        "#;
+
+        assert_deserialized(&TomlDeserializer.deserialize(toml.as_bytes()).unwrap());
+    }
+}
@@ -0,0 +1,352 @@
+use std::{collections::BTreeMap, io::Cursor};
+
+use bevy_app::prelude::*;
+use bevy_asset::{io::Reader, prelude::*, AssetLoader, LoadContext, RenderAssetUsages};
+use bevy_image::TextureAtlasBuilderError;
+use bevy_math::UVec2;
+use bevy_reflect::prelude::*;
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, DynamicImage, Frame, ImageError, ImageFormat, ImageResult,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    ani::{
+        animation::{Animation, AnimationRepeat},
+        asset::AnimatedCursor,
+    },
+    atlas::{build_cursor_atlas, image_from_rgba},
+    variant::CursorVariant,
+};
+
+/// How long frames without a delay are displayed for, in milliseconds, like
+/// browsers do for GIFs.
+const DEFAULT_FRAME_DELAY: u32 = 100;
+
+pub struct AnimatedImageCursorAssetPlugin;
+
+impl Plugin for AnimatedImageCursorAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset_loader::<AnimatedImageCursorLoader>();
+    }
+}
+
+/// A loader for animated cursor assets from animated GIF, APNG and WebP files.
+///
+/// Each frame of the image becomes a frame of the cursor, displayed for the
+/// frame's delay. Images don't have hotspots, so set one with
+/// [`AnimatedImageCursorLoaderSettings::hotspot`], e.g. in a `.meta` file next
+/// to the image.
+///
+/// APNG files must use the `.apng` extension, since `.png` files are loaded as
+/// images.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct AnimatedImageCursorLoader;
+
+/// Settings for loading animated cursors with [`AnimatedImageCursorLoader`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AnimatedImageCursorLoaderSettings {
+    /// The hotspot of every frame, in pixels from the top left corner.
+    pub hotspot: (u16, u16),
+    /// The usage of the texture atlas image.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+    /// Whether to keep the image of each frame as an `image_{index}` labeled
+    /// asset, where `index` is the frame's atlas index.
+    ///
+    /// Defaults to `true`.
+    pub keep_frame_images: bool,
+    /// If set, overrides the duration of every frame, in milliseconds.
+    pub frame_duration: Option<u32>,
+    /// If set, overrides the repeat behavior of the animation, which loops by
+    /// default.
+    pub repeat: Option<AnimationRepeat>,
+}
+
+impl Default for AnimatedImageCursorLoaderSettings {
+    fn default() -> Self {
+        Self {
+            hotspot: (0, 0),
+            asset_usage: RenderAssetUsages::default(),
+            keep_frame_images: true,
+            frame_duration: None,
+            repeat: None,
+        }
+    }
+}
+
+/// Possible errors that can be produced by [`AnimatedImageCursorLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AnimatedImageCursorLoaderError {
+    /// An [IO](std::io) error.
+    #[error("could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// An [ImageError] error.
+    #[error("could not decode image: {0}")]
+    ImageError(#[from] ImageError),
+    #[error("unsupported image format: {0:?}")]
+    UnsupportedFormat(ImageFormat),
+    #[error("image has no frames")]
+    MissingFrames,
+    #[error("hotspot ({0}, {1}) is outside of the image")]
+    InvalidHotspot(u16, u16),
+    #[error("could not create image buffer")]
+    ImageBufferError,
+    #[error("could not build texture atlas: {0}")]
+    TextureAtlasBuilderError(#[from] TextureAtlasBuilderError),
+}
+
+/// Decodes the frames of an animated GIF, APNG or WebP image. Images that
+/// aren't animated have a single frame.
+fn decode_frames(bytes: &[u8]) -> Result<Vec<Frame>, AnimatedImageCursorLoaderError> {
+    let single = |image: ImageResult<DynamicImage>| -> ImageResult<Vec<Frame>> {
+        Ok(vec![Frame::new(image?.into_rgba8())])
+    };
+
+    let reader = Cursor::new(bytes);
+    let frames = match image::guess_format(bytes)? {
+        ImageFormat::Gif => GifDecoder::new(reader)?.into_frames().collect_frames(),
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(reader)?;
+            if decoder.is_apng()? {
+                decoder.apng()?.into_frames().collect_frames()
+            } else {
+                single(DynamicImage::from_decoder(decoder))
+            }
+        }
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(reader)?;
+            if decoder.has_animation() {
+                decoder.into_frames().collect_frames()
+            } else {
+                single(DynamicImage::from_decoder(decoder))
+            }
+        }
+        format => return Err(AnimatedImageCursorLoaderError::UnsupportedFormat(format)),
+    }?;
+
+    if frames.is_empty() {
+        return Err(AnimatedImageCursorLoaderError::MissingFrames);
+    }
+
+    Ok(frames)
+}
+
+/// Returns the delay of `frame` in milliseconds.
+fn frame_delay(frame: &Frame) -> u32 {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    match numer.checked_div(denom) {
+        Some(0) | None => DEFAULT_FRAME_DELAY,
+        Some(delay) => delay,
+    }
+}
+
+impl AssetLoader for AnimatedImageCursorLoader {
+    type Asset = AnimatedCursor;
+    type Settings = AnimatedImageCursorLoaderSettings;
+    type Error = AnimatedImageCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let frames = decode_frames(&bytes)?;
+
+        // Frames are composited onto the full canvas, so they all have the
+        // same size.
+        let (width, height) = frames[0].buffer().dimensions();
+        let (x, y) = settings.hotspot;
+        if x as u32 >= width || y as u32 >= height {
+            return Err(AnimatedImageCursorLoaderError::InvalidHotspot(x, y));
+        }
+
+        let animation_frames = frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| (i, settings.frame_duration.unwrap_or(frame_delay(frame))))
+            .collect::<Vec<_>>();
+
+        let images = frames
+            .into_iter()
+            .map(|frame| {
+                let image = frame.into_buffer();
+                image_from_rgba(image.width(), image.height(), image.into_raw())
+                    .map(|image| (image, settings.hotspot))
+                    .ok_or(AnimatedImageCursorLoaderError::ImageBufferError)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let atlas = build_cursor_atlas(
+            load_context,
+            "",
+            images,
+            settings.keep_frame_images,
+            settings.asset_usage,
+        )?;
+
+        let mut animation = Animation::from_frame_durations(animation_frames);
+        if let Some(repeat) = settings.repeat {
+            animation.repeat = repeat;
+        }
+
+        Ok(AnimatedCursor {
+            metadata: None,
+            info: None,
+            image: atlas.image,
+            texture_atlas_layout: atlas.texture_atlas_layout,
+            hotspots: atlas.hotspots,
+            variants: vec![CursorVariant {
+                size: UVec2::new(width, height),
+                atlas_offset: 0,
+            }],
+            animation,
+            animations: BTreeMap::new(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gif", "apng", "webp"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bevy_image::{Image, TextureAtlasLayout};
+    use image::{codecs::gif::GifEncoder, Delay, Rgba, RgbaImage};
+
+    use crate::testing::{create_app, load_with_settings};
+
+    use super::*;
+
+    /// Encodes an 8 by 8 GIF with a frame for each of `delays`, in
+    /// milliseconds, alternating between red and blue.
+    fn gif_bytes(delays: &[u32]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        GifEncoder::new(&mut bytes)
+            .encode_frames(delays.iter().enumerate().map(|(i, &delay)| {
+                let color = if i % 2 == 0 {
+                    Rgba([255, 0, 0, 255])
+                } else {
+                    Rgba([0, 0, 255, 255])
+                };
+                Frame::from_parts(
+                    RgbaImage::from_pixel(8, 8, color),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(delay, 1),
+                )
+            }))
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_decode_gif_frames() {
+        let frames = decode_frames(&gif_bytes(&[50, 0])).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].buffer().dimensions(), (8, 8));
+        assert_eq!(frames[1].buffer().get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(frame_delay(&frames[0]), 50);
+        assert_eq!(frame_delay(&frames[1]), DEFAULT_FRAME_DELAY);
+    }
+
+    #[test]
+    fn test_load_with_default_settings() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.gif"), gif_bytes(&[50, 0, 30]));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedImageCursorLoaderSettings>(
+            &mut app,
+            "cursor.gif",
+            |settings| settings.hotspot = (3, 7),
+        )
+        .unwrap();
+
+        let world = app.world();
+        let cursor = world
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        let layout = world
+            .resource::<Assets<TextureAtlasLayout>>()
+            .get(&cursor.texture_atlas_layout)
+            .unwrap();
+        assert_eq!(layout.textures.len(), 3);
+        assert_eq!(layout.textures[2].size(), UVec2::splat(8));
+        assert_eq!(cursor.hotspot_or_default(2), (3, 7));
+        assert_eq!(
+            cursor.variants,
+            vec![CursorVariant {
+                size: UVec2::splat(8),
+                atlas_offset: 0,
+            }]
+        );
+        // Frames without a delay are displayed for the default delay.
+        assert_eq!(
+            cursor.animation,
+            Animation::from_frame_durations([(0, 50), (1, DEFAULT_FRAME_DELAY), (2, 30)])
+        );
+
+        let image = load_with_settings::<Image, AnimatedImageCursorLoaderSettings>(
+            &mut app,
+            "cursor.gif#image_1",
+            |settings| settings.hotspot = (3, 7),
+        )
+        .unwrap();
+        let image = app.world().resource::<Assets<Image>>().get(&image).unwrap();
+        assert_eq!(image.size(), UVec2::splat(8));
+    }
+
+    #[test]
+    fn test_load_with_settings() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.gif"), gif_bytes(&[50, 0]));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedImageCursorLoaderSettings>(
+            &mut app,
+            "cursor.gif",
+            |settings| {
+                settings.frame_duration = Some(20);
+                settings.repeat = Some(AnimationRepeat::Times(2));
+            },
+        )
+        .unwrap();
+
+        let cursor = app
+            .world()
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        let mut animation = Animation::from_frame_durations([(0, 20), (1, 20)]);
+        animation.repeat = AnimationRepeat::Times(2);
+        assert_eq!(cursor.animation, animation);
+    }
+
+    #[test]
+    fn test_load_invalid_hotspot() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.gif"), gif_bytes(&[50]));
+
+        let error = load_with_settings::<AnimatedCursor, AnimatedImageCursorLoaderSettings>(
+            &mut app,
+            "cursor.gif",
+            |settings| settings.hotspot = (8, 0),
+        )
+        .unwrap_err();
+
+        assert!(error.contains("hotspot (8, 0) is outside of the image"));
+    }
+}
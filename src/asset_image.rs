@@ -57,7 +57,7 @@ pub(crate) async fn load_image(
     ))
 }
 
-fn process_image(
+pub(crate) fn process_image(
     src_img: DynamicImage,
     color_key: Option<(u8, u8, u8)>,
     flip_x: bool,
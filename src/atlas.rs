@@ -0,0 +1,77 @@
+use bevy_asset::{prelude::*, LoadContext, RenderAssetUsages};
+use bevy_image::{Image, TextureAtlasBuilder, TextureAtlasBuilderError, TextureAtlasLayout};
+use image::{DynamicImage, RgbaImage};
+
+use crate::hotspot::CursorHotspots;
+
+/// The texture atlas of a cursor, added to a load context as labeled assets.
+pub(crate) struct CursorAtlas {
+    pub image: Handle<Image>,
+    pub texture_atlas_layout: Handle<TextureAtlasLayout>,
+    pub hotspots: CursorHotspots,
+}
+
+/// Creates an [`Image`] from straight alpha RGBA pixels, or returns `None` if
+/// there aren't `width * height` pixels.
+pub(crate) fn image_from_rgba(width: u32, height: u32, rgba: Vec<u8>) -> Option<Image> {
+    RgbaImage::from_raw(width, height, rgba).map(|image| {
+        Image::from_dynamic(
+            DynamicImage::ImageRgba8(image),
+            true,
+            RenderAssetUsages::MAIN_WORLD,
+        )
+    })
+}
+
+/// Packs `frames` into a texture atlas, in order, so that each frame's index
+/// is its atlas index.
+///
+/// The atlas is added as the `image` and `texture_atlas_layout` labeled
+/// assets. If `keep_images` is set, each frame is also added as an
+/// `image_{index}` labeled asset. Labels start with `label_prefix`, so that
+/// several cursors can be added to the same load context.
+pub(crate) fn build_cursor_atlas(
+    load_context: &mut LoadContext<'_>,
+    label_prefix: &str,
+    frames: Vec<(Image, (u16, u16))>,
+    keep_images: bool,
+    asset_usage: RenderAssetUsages,
+) -> Result<CursorAtlas, TextureAtlasBuilderError> {
+    let handles = frames
+        .iter()
+        .enumerate()
+        .map(|(i, (image, _))| {
+            keep_images.then(|| {
+                load_context
+                    .add_labeled_asset(format!("{}image_{}", label_prefix, i), image.clone())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut texture_atlas_builder = TextureAtlasBuilder::default();
+    for ((image, _), handle) in frames.iter().zip(&handles) {
+        texture_atlas_builder.add_texture(handle.as_ref().map(|h| h.id()), image);
+    }
+
+    let (texture_atlas_layout, _, mut image) = texture_atlas_builder.build()?;
+    image.asset_usage = asset_usage;
+
+    // Every frame gets an override, like the .CUR and .ANI loaders.
+    let hotspots = CursorHotspots {
+        overrides: frames
+            .iter()
+            .enumerate()
+            .map(|(i, (_, hotspot))| (i, *hotspot))
+            .collect(),
+        ..Default::default()
+    };
+
+    Ok(CursorAtlas {
+        image: load_context.add_labeled_asset(format!("{}image", label_prefix), image),
+        texture_atlas_layout: load_context.add_labeled_asset(
+            format!("{}texture_atlas_layout", label_prefix),
+            texture_atlas_layout,
+        ),
+        hotspots,
+    })
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use bevy_math::UVec2;
+use image::{imageops, DynamicImage, RgbaImage};
+
+use crate::{asset_image::process_image, hotspot::CursorHotspots};
+
+/// A grid of equally sized tiles on a sprite sheet, laid out the same way as
+/// [`TextureAtlasLayout::from_grid`](bevy_image::TextureAtlasLayout::from_grid).
+pub(crate) struct Grid {
+    pub tile_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+    pub padding: UVec2,
+    pub offset: UVec2,
+}
+
+impl Grid {
+    /// Returns the tile at `index`, or `None` if the index is outside of the
+    /// grid or the tile is outside of `image`.
+    pub fn tile(&self, image: &RgbaImage, index: usize) -> Option<RgbaImage> {
+        if self.columns == 0 || index >= (self.columns * self.rows) as usize {
+            return None;
+        }
+
+        let cell = UVec2::new(index as u32 % self.columns, index as u32 / self.columns);
+        let min = (self.tile_size + self.padding) * cell + self.offset;
+        let max = min + self.tile_size;
+
+        if max.x > image.width() || max.y > image.height() {
+            return None;
+        }
+
+        Some(imageops::crop_imm(image, min.x, min.y, self.tile_size.x, self.tile_size.y).to_image())
+    }
+}
+
+/// Prepares a sprite sheet for slicing, applying the same color key and flips
+/// as the text format loaders.
+pub(crate) fn process_sheet(
+    image: &DynamicImage,
+    color_key: Option<(u8, u8, u8)>,
+    flip_x: bool,
+    flip_y: bool,
+) -> RgbaImage {
+    process_image(image.clone(), color_key, flip_x, flip_y).into_rgba8()
+}
+
+/// Packs `tiles` into a single row, returning the sprite sheet and its grid.
+///
+/// Tiles smaller than the largest tile are placed in the top-left corner of
+/// their cell, so their hotspots don't change.
+pub(crate) fn pack_tiles(tiles: &[RgbaImage]) -> (RgbaImage, Grid) {
+    let tile_size = tiles
+        .iter()
+        .map(|t| UVec2::new(t.width(), t.height()))
+        .fold(UVec2::ONE, UVec2::max);
+
+    let mut sheet = RgbaImage::new(tile_size.x * tiles.len().max(1) as u32, tile_size.y);
+    for (i, tile) in tiles.iter().enumerate() {
+        imageops::replace(&mut sheet, tile, (i as u32 * tile_size.x) as i64, 0);
+    }
+
+    (
+        sheet,
+        Grid {
+            tile_size,
+            columns: tiles.len() as u32,
+            rows: 1,
+            padding: UVec2::ZERO,
+            offset: UVec2::ZERO,
+        },
+    )
+}
+
+/// Returns hotspots whose default is the first hotspot, with overrides for the
+/// tiles whose hotspot differs.
+pub(crate) fn hotspots_from_tiles(hotspots: &[(u16, u16)]) -> CursorHotspots {
+    let default = hotspots.first().copied().unwrap_or_default();
+
+    CursorHotspots {
+        default,
+        overrides: hotspots
+            .iter()
+            .enumerate()
+            .filter(|(_, hotspot)| **hotspot != default)
+            .map(|(i, hotspot)| (i, *hotspot))
+            .collect::<HashMap<_, _>>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_padding_and_offset() {
+        // Each pixel stores its own coordinates, so tiles show where they were
+        // cropped from.
+        let image = RgbaImage::from_fn(32, 32, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+        let grid = Grid {
+            tile_size: UVec2::new(4, 6),
+            columns: 3,
+            rows: 2,
+            padding: UVec2::new(2, 1),
+            offset: UVec2::new(5, 3),
+        };
+
+        let origin = |index| grid.tile(&image, index).map(|tile| tile.get_pixel(0, 0).0);
+        assert_eq!(origin(0), Some([5, 3, 0, 255]));
+        assert_eq!(origin(1), Some([11, 3, 0, 255]));
+        assert_eq!(origin(2), Some([17, 3, 0, 255]));
+        assert_eq!(origin(4), Some([11, 10, 0, 255]));
+
+        let tile = grid.tile(&image, 5).unwrap();
+        assert_eq!(tile.dimensions(), (4, 6));
+        assert_eq!(tile.get_pixel(3, 5).0, [20, 15, 0, 255]);
+
+        // Indices outside of the grid have no tile.
+        assert_eq!(origin(6), None);
+
+        // Nor do tiles that the offset pushes outside of the image.
+        let grid = Grid {
+            offset: UVec2::new(17, 0),
+            ..grid
+        };
+        assert!(grid.tile(&image, 1).is_some());
+        assert!(grid.tile(&image, 2).is_none());
+    }
+}
@@ -4,13 +4,17 @@ use bevy_image::{Image, TextureAtlasBuilder, TextureAtlasBuilderError, TextureAt
 use bevy_reflect::prelude::*;
 use ico::ResourceType;
 use image::{DynamicImage, ImageBuffer};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     cur::decoder::{DecodeError, Decoder},
     hotspot::CursorHotspots,
+    variant::{best_size_index, best_variant, size_for_scale_factor, CursorVariant},
 };
 
+#[cfg(feature = "packed_asset")]
+use super::packed_asset::PackedStaticCursorLoader;
 #[cfg(feature = "serde_json_asset")]
 use super::serde_asset::JsonDeserializer;
 #[cfg(feature = "serde_ron_asset")]
@@ -46,6 +50,9 @@ impl Plugin for StaticCursorAssetPlugin {
             }
         }
 
+        #[cfg(feature = "packed_asset")]
+        app.init_asset_loader::<PackedStaticCursorLoader>();
+
         app.init_asset::<StaticCursor>()
             .init_asset_loader::<StaticCursorLoader>()
             .register_asset_reflect::<StaticCursor>();
@@ -61,6 +68,12 @@ pub struct StaticCursor {
     pub texture_atlas_layout: Handle<TextureAtlasLayout>,
     /// The hotspot data.
     pub hotspots: CursorHotspots,
+    /// The size of each entry, as a variant with one frame whose
+    /// `atlas_offset` is the entry's atlas index.
+    ///
+    /// Empty if the sizes are not known, e.g. for cursors loaded from text
+    /// formats.
+    pub variants: Vec<CursorVariant>,
 }
 
 impl StaticCursor {
@@ -74,6 +87,23 @@ impl StaticCursor {
     pub fn hotspot_or_default(&self, index: usize) -> (u16, u16) {
         self.hotspots.get_or_default(index)
     }
+
+    /// Returns the atlas index of the entry whose size best matches `size`, in
+    /// pixels, or `None` if the entry sizes are not known.
+    ///
+    /// The smallest entry that is at least `size` pixels is preferred. If every
+    /// entry is smaller, the largest entry is used.
+    pub fn index_for_size(&self, size: u32) -> Option<usize> {
+        best_variant(&self.variants, size).map(|v| v.atlas_offset)
+    }
+
+    /// Returns the atlas index of the entry that best suits a window with the
+    /// given scale factor, or `None` if the entry sizes are not known.
+    ///
+    /// See [`size_for_scale_factor`] for how the scale factor maps to a size.
+    pub fn index_for_scale_factor(&self, scale_factor: f32) -> Option<usize> {
+        self.index_for_size(size_for_scale_factor(scale_factor))
+    }
 }
 
 /// A loader for static cursor assets from .CUR files.
@@ -81,6 +111,34 @@ impl StaticCursor {
 #[reflect(Debug, Default)]
 pub struct StaticCursorLoader;
 
+/// Settings for loading static cursors with [`StaticCursorLoader`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StaticCursorLoaderSettings {
+    /// If set, only the entry whose size best matches this size, in pixels, is
+    /// loaded. Otherwise, every entry is loaded.
+    pub preferred_size: Option<u32>,
+    /// The usage of the texture atlas image.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+    /// Whether to keep the image of each entry as an `image_{index}` labeled
+    /// asset, where `index` is the entry's atlas index.
+    ///
+    /// Defaults to `true`.
+    pub keep_entry_images: bool,
+}
+
+impl Default for StaticCursorLoaderSettings {
+    fn default() -> Self {
+        Self {
+            preferred_size: None,
+            asset_usage: RenderAssetUsages::default(),
+            keep_entry_images: true,
+        }
+    }
+}
+
 /// Possible errors that can be produced by [`StaticCursorLoader`].
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -103,12 +161,12 @@ pub enum StaticCursorLoaderError {
 
 impl AssetLoader for StaticCursorLoader {
     type Asset = StaticCursor;
-    type Settings = ();
+    type Settings = StaticCursorLoaderSettings;
     type Error = StaticCursorLoaderError;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
@@ -120,58 +178,77 @@ impl AssetLoader for StaticCursorLoader {
 
         let c = decoder.decode()?;
 
-        let items =
-            c.0.entries()
-                .iter()
-                .enumerate()
-                .map(|(i, e)| {
-                    if e.resource_type() != ResourceType::Cursor {
-                        return Err(StaticCursorLoaderError::InvalidResourceType(format!(
-                            "{:?}",
-                            e.resource_type()
-                        )));
-                    }
-
-                    let icon_image = e.decode()?;
-
-                    let image = ImageBuffer::from_raw(
-                        icon_image.width(),
-                        icon_image.height(),
-                        icon_image.rgba_data().to_vec(),
-                    )
-                    .map(DynamicImage::ImageRgba8)
-                    .ok_or(StaticCursorLoaderError::ImageBufferError)?;
-
-                    let image = Image::from_dynamic(image, true, RenderAssetUsages::MAIN_WORLD);
-
-                    let hotspot = icon_image
-                        .cursor_hotspot()
-                        .ok_or(StaticCursorLoaderError::MissingHotspot)?;
-
-                    Ok((
-                        (
-                            load_context.labeled_asset_scope(
-                                format!("image_{}", i).to_string(),
-                                |_| -> Result<Image, StaticCursorLoaderError> { Ok(image.clone()) },
-                            )?,
-                            image,
-                        ),
-                        hotspot,
-                    ))
-                })
-                .collect::<Result<Vec<_>, StaticCursorLoaderError>>()?;
+        let entries = match settings.preferred_size {
+            Some(size) => best_size_index(
+                c.0.entries().iter().map(|e| e.width().max(e.height())),
+                size,
+            )
+            .map(|i| vec![&c.0.entries()[i]])
+            .unwrap_or_default(),
+            None => c.0.entries().iter().collect(),
+        };
+
+        let items = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| {
+                if e.resource_type() != ResourceType::Cursor {
+                    return Err(StaticCursorLoaderError::InvalidResourceType(format!(
+                        "{:?}",
+                        e.resource_type()
+                    )));
+                }
+
+                let icon_image = e.decode()?;
+
+                let image = ImageBuffer::from_raw(
+                    icon_image.width(),
+                    icon_image.height(),
+                    icon_image.rgba_data().to_vec(),
+                )
+                .map(DynamicImage::ImageRgba8)
+                .ok_or(StaticCursorLoaderError::ImageBufferError)?;
+
+                let image = Image::from_dynamic(image, true, RenderAssetUsages::MAIN_WORLD);
+
+                let hotspot = icon_image
+                    .cursor_hotspot()
+                    .ok_or(StaticCursorLoaderError::MissingHotspot)?;
+
+                let handle = if settings.keep_entry_images {
+                    Some(load_context.labeled_asset_scope(
+                        format!("image_{}", i).to_string(),
+                        |_| -> Result<Image, StaticCursorLoaderError> { Ok(image.clone()) },
+                    )?)
+                } else {
+                    None
+                };
+
+                Ok(((handle, image), hotspot))
+            })
+            .collect::<Result<Vec<_>, StaticCursorLoaderError>>()?;
+
+        let variants = items
+            .iter()
+            .enumerate()
+            .map(|(i, ((_, image), _))| CursorVariant {
+                size: image.size(),
+                atlas_offset: i,
+            })
+            .collect();
 
         let mut texture_atlas_builder = TextureAtlasBuilder::default();
 
         let mut hotspots = Vec::new();
 
         for ((handle, image), hotspot) in items.iter() {
-            texture_atlas_builder.add_texture(Some(handle.id()), image);
+            texture_atlas_builder.add_texture(handle.as_ref().map(|h| h.id()), image);
 
             hotspots.push(*hotspot);
         }
 
-        let (texture_atlas_layout, _, image) = texture_atlas_builder.build()?;
+        let (texture_atlas_layout, _, mut image) = texture_atlas_builder.build()?;
+        image.asset_usage = settings.asset_usage;
 
         let texture_atlas_layout = load_context.labeled_asset_scope(
             "texture_atlas_layout".to_string(),
@@ -200,6 +277,7 @@ impl AssetLoader for StaticCursorLoader {
             image,
             texture_atlas_layout,
             hotspots,
+            variants,
         })
     }
 
@@ -207,3 +285,151 @@ impl AssetLoader for StaticCursorLoader {
         &["CUR", "cur"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bevy_math::UVec2;
+
+    use crate::testing::{create_app, cur_bytes, load_with_settings};
+
+    use super::*;
+
+    #[test]
+    fn test_load_with_default_settings() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.cur"), cur_bytes(&[16, 32]));
+
+        let handle = load_with_settings::<StaticCursor, StaticCursorLoaderSettings>(
+            &mut app,
+            "cursor.cur",
+            |_| {},
+        )
+        .unwrap();
+
+        let world = app.world();
+        let cursor = world
+            .resource::<Assets<StaticCursor>>()
+            .get(&handle)
+            .unwrap();
+        let layout = world
+            .resource::<Assets<TextureAtlasLayout>>()
+            .get(&cursor.texture_atlas_layout)
+            .unwrap();
+        assert_eq!(layout.textures.len(), 2);
+        assert_eq!(cursor.hotspots.get_or_default(1), (16, 16));
+        assert_eq!(
+            cursor.variants,
+            vec![
+                CursorVariant {
+                    size: UVec2::splat(16),
+                    atlas_offset: 0,
+                },
+                CursorVariant {
+                    size: UVec2::splat(32),
+                    atlas_offset: 1,
+                },
+            ]
+        );
+
+        assert_eq!(
+            world
+                .resource::<Assets<Image>>()
+                .get(&cursor.image)
+                .unwrap()
+                .asset_usage,
+            RenderAssetUsages::default()
+        );
+
+        let image = load_with_settings::<Image, StaticCursorLoaderSettings>(
+            &mut app,
+            "cursor.cur#image_1",
+            |_| {},
+        )
+        .unwrap();
+        let image = app.world().resource::<Assets<Image>>().get(&image).unwrap();
+        assert_eq!(image.size(), UVec2::splat(32));
+    }
+
+    #[test]
+    fn test_load_with_settings() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.cur"), cur_bytes(&[16, 32, 48]));
+
+        let handle = load_with_settings::<StaticCursor, StaticCursorLoaderSettings>(
+            &mut app,
+            "cursor.cur",
+            |settings| {
+                settings.preferred_size = Some(30);
+                settings.asset_usage = RenderAssetUsages::MAIN_WORLD;
+                settings.keep_entry_images = false;
+            },
+        )
+        .unwrap();
+
+        let world = app.world();
+        let cursor = world
+            .resource::<Assets<StaticCursor>>()
+            .get(&handle)
+            .unwrap();
+        let layout = world
+            .resource::<Assets<TextureAtlasLayout>>()
+            .get(&cursor.texture_atlas_layout)
+            .unwrap();
+        assert_eq!(layout.textures.len(), 1);
+        assert_eq!(layout.textures[0].size(), UVec2::splat(32));
+        assert_eq!(cursor.hotspots.get_or_default(0), (16, 16));
+
+        assert_eq!(
+            world
+                .resource::<Assets<Image>>()
+                .get(&cursor.image)
+                .unwrap()
+                .asset_usage,
+            RenderAssetUsages::MAIN_WORLD
+        );
+
+        // Entry images aren't kept.
+        assert!(load_with_settings::<Image, StaticCursorLoaderSettings>(
+            &mut app,
+            "cursor.cur#image_0",
+            |settings| settings.keep_entry_images = false,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_index_for_size() {
+        let cursor = |sizes: &[u32]| StaticCursor {
+            image: Handle::default(),
+            texture_atlas_layout: Handle::default(),
+            hotspots: CursorHotspots::default(),
+            variants: sizes
+                .iter()
+                .enumerate()
+                .map(|(i, &size)| CursorVariant {
+                    size: UVec2::splat(size),
+                    atlas_offset: i,
+                })
+                .collect(),
+        };
+
+        let c = cursor(&[48, 32, 64, 32]);
+        // Exact matches, and the first of entries with the same size.
+        assert_eq!(c.index_for_size(48), Some(0));
+        assert_eq!(c.index_for_size(32), Some(1));
+        // The nearest larger entry, or the largest entry.
+        assert_eq!(c.index_for_size(40), Some(0));
+        assert_eq!(c.index_for_size(16), Some(1));
+        assert_eq!(c.index_for_size(128), Some(2));
+
+        assert_eq!(c.index_for_scale_factor(1.0), Some(1));
+        assert_eq!(c.index_for_scale_factor(1.5), Some(0));
+        assert_eq!(c.index_for_scale_factor(2.0), Some(2));
+
+        let c = cursor(&[]);
+        assert_eq!(c.index_for_size(32), None);
+        assert_eq!(c.index_for_scale_factor(1.0), None);
+    }
+}
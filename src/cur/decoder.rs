@@ -3,7 +3,8 @@ use std::{
     io::{Error as IoError, Read, Seek},
 };
 
-use ico::IconDir;
+use ico::{IconDir, IconDirEntry};
+use image::RgbaImage;
 
 use super::*;
 
@@ -46,3 +47,18 @@ impl<R: Read + Seek> Decoder<R> {
         Ok(StaticCursor(icon))
     }
 }
+
+/// Decodes an icon entry to an RGBA image and its hotspot.
+pub(crate) fn decode_entry(entry: &IconDirEntry) -> std::io::Result<(RgbaImage, (u16, u16))> {
+    let icon_image = entry.decode()?;
+    let hotspot = icon_image.cursor_hotspot().unwrap_or_default();
+
+    let image = RgbaImage::from_raw(
+        icon_image.width(),
+        icon_image.height(),
+        icon_image.rgba_data().to_vec(),
+    )
+    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid image data"))?;
+
+    Ok((image, hotspot))
+}
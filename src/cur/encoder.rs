@@ -0,0 +1,122 @@
+use std::{
+    fmt,
+    io::{Error as IoError, Write},
+};
+
+use ico::{IconDir, IconDirEntry, IconImage, ResourceType};
+use image::RgbaImage;
+
+use super::*;
+
+/// The largest width and height of an image in a .CUR file, in pixels.
+pub const MAX_IMAGE_SIZE: u32 = 256;
+
+#[derive(Debug)]
+pub enum EncodeError {
+    IoError(IoError),
+    NoImages,
+    InvalidResourceType(ResourceType),
+    InvalidImageSize(u32, u32),
+    InvalidHotspot(u16, u16),
+}
+
+impl std::error::Error for EncodeError {}
+
+impl From<IoError> for EncodeError {
+    fn from(error: IoError) -> Self {
+        EncodeError::IoError(error)
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::IoError(e) => write!(f, "IO error: {}", e),
+            EncodeError::NoImages => write!(f, "a cursor must have at least one image"),
+            EncodeError::InvalidResourceType(resource_type) => {
+                write!(
+                    f,
+                    "resource type must be cursor, found: {:?}",
+                    resource_type
+                )
+            }
+            EncodeError::InvalidImageSize(width, height) => write!(
+                f,
+                "invalid image size {}x{}, must be between 1x1 and {}x{}",
+                width, height, MAX_IMAGE_SIZE, MAX_IMAGE_SIZE
+            ),
+            EncodeError::InvalidHotspot(x, y) => {
+                write!(f, "hotspot ({}, {}) is outside of the image", x, y)
+            }
+        }
+    }
+}
+
+impl StaticCursor {
+    /// Creates a static cursor from RGBA images and their hotspots.
+    ///
+    /// Each image becomes one entry of the cursor, e.g. the same cursor at
+    /// several sizes.
+    pub fn from_images<'a>(
+        images: impl IntoIterator<Item = (&'a RgbaImage, (u16, u16))>,
+    ) -> Result<Self, EncodeError> {
+        let mut icon_dir = IconDir::new(ResourceType::Cursor);
+
+        for (image, (x, y)) in images {
+            let (width, height) = image.dimensions();
+            if !(1..=MAX_IMAGE_SIZE).contains(&width) || !(1..=MAX_IMAGE_SIZE).contains(&height) {
+                return Err(EncodeError::InvalidImageSize(width, height));
+            }
+            if x as u32 >= width || y as u32 >= height {
+                return Err(EncodeError::InvalidHotspot(x, y));
+            }
+
+            let mut icon_image = IconImage::from_rgba_data(width, height, image.as_raw().clone());
+            icon_image.set_cursor_hotspot(Some((x, y)));
+
+            icon_dir.add_entry(IconDirEntry::encode(&icon_image)?);
+        }
+
+        if icon_dir.entries().is_empty() {
+            return Err(EncodeError::NoImages);
+        }
+
+        Ok(StaticCursor(icon_dir))
+    }
+}
+
+pub struct Encoder<W>
+where
+    W: Write,
+{
+    writer: W,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W) -> Self {
+        Encoder { writer }
+    }
+
+    pub fn encode(&mut self, cursor: &StaticCursor) -> Result<(), EncodeError> {
+        if cursor.0.resource_type() != ResourceType::Cursor {
+            return Err(EncodeError::InvalidResourceType(cursor.0.resource_type()));
+        }
+        if cursor.0.entries().is_empty() {
+            return Err(EncodeError::NoImages);
+        }
+
+        cursor.0.write(&mut self.writer)?;
+
+        Ok(())
+    }
+
+    /// Encodes RGBA images and their hotspots as a static cursor.
+    ///
+    /// See [`StaticCursor::from_images`].
+    pub fn encode_images<'a>(
+        &mut self,
+        images: impl IntoIterator<Item = (&'a RgbaImage, (u16, u16))>,
+    ) -> Result<(), EncodeError> {
+        self.encode(&StaticCursor::from_images(images)?)
+    }
+}
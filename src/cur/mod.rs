@@ -1,7 +1,10 @@
 pub mod asset;
 pub mod decoder;
+pub mod encoder;
+#[cfg(feature = "packed_asset")]
+pub mod packed_asset;
 #[cfg(feature = "serde_asset")]
-mod serde_asset;
+pub mod serde_asset;
 
 use ico::IconDir;
 #[cfg(feature = "serde")]
@@ -20,9 +23,13 @@ mod tests {
         path::{Path, PathBuf},
     };
 
-    use ico::ResourceType;
+    use ico::{IconDir, ResourceType};
 
-    use crate::cur::decoder::Decoder;
+    use crate::cur::{
+        decoder::Decoder,
+        encoder::{EncodeError, Encoder, MAX_IMAGE_SIZE},
+        StaticCursor,
+    };
 
     #[test]
     fn test_decode_hand_cur() {
@@ -53,6 +60,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encode_round_trip() {
+        let small =
+            image::RgbaImage::from_fn(16, 16, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+        let large =
+            image::RgbaImage::from_fn(32, 32, |x, y| image::Rgba([0, x as u8, y as u8, 128]));
+
+        let mut bytes = Vec::new();
+        Encoder::new(&mut bytes)
+            .encode_images([(&small, (3, 4)), (&large, (6, 8))])
+            .unwrap();
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert_eq!(cursor.0.resource_type(), ResourceType::Cursor);
+        assert_eq!(cursor.0.entries().len(), 2);
+
+        for (entry, (image, hotspot)) in cursor
+            .0
+            .entries()
+            .iter()
+            .zip([(&small, (3, 4)), (&large, (6, 8))])
+        {
+            assert_eq!(entry.resource_type(), ResourceType::Cursor);
+
+            let icon_image = entry.decode().unwrap();
+            assert_eq!(icon_image.width(), image.width());
+            assert_eq!(icon_image.height(), image.height());
+            assert_eq!(icon_image.cursor_hotspot(), Some(hotspot));
+            assert_eq!(icon_image.rgba_data(), image.as_raw().as_slice());
+        }
+
+        // Re-encoding the decoded cursor gives the same bytes.
+        let mut first = Vec::new();
+        Encoder::new(&mut first).encode(&cursor).unwrap();
+        let decoded = Decoder::new(std::io::Cursor::new(first.clone()))
+            .decode()
+            .unwrap();
+        let mut second = Vec::new();
+        Encoder::new(&mut second).encode(&decoded).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let encode_images = |images: &[(&image::RgbaImage, (u16, u16))]| {
+            let mut bytes = Vec::new();
+            let result = Encoder::new(&mut bytes).encode_images(images.iter().copied());
+            // Nothing is written for cursors that can't be encoded.
+            assert!(bytes.is_empty());
+            result
+        };
+
+        assert!(matches!(encode_images(&[]), Err(EncodeError::NoImages)));
+
+        let oversized = image::RgbaImage::new(MAX_IMAGE_SIZE + 1, 16);
+        assert!(matches!(
+            encode_images(&[(&oversized, (0, 0))]),
+            Err(EncodeError::InvalidImageSize(257, 16))
+        ));
+        let empty = image::RgbaImage::new(0, 0);
+        assert!(matches!(
+            encode_images(&[(&empty, (0, 0))]),
+            Err(EncodeError::InvalidImageSize(0, 0))
+        ));
+
+        let image = image::RgbaImage::new(16, 16);
+        assert!(matches!(
+            encode_images(&[(&image, (0, 0)), (&image, (16, 2))]),
+            Err(EncodeError::InvalidHotspot(16, 2))
+        ));
+
+        let mut bytes = Vec::new();
+        assert!(matches!(
+            Encoder::new(&mut bytes).encode(&StaticCursor(IconDir::new(ResourceType::Cursor))),
+            Err(EncodeError::NoImages)
+        ));
+        assert!(matches!(
+            Encoder::new(&mut bytes).encode(&StaticCursor(IconDir::new(ResourceType::Icon))),
+            Err(EncodeError::InvalidResourceType(ResourceType::Icon))
+        ));
+        assert!(bytes.is_empty());
+    }
+
     #[test]
     fn test_decode_all() {
         let d: PathBuf = [
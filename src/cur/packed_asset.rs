@@ -0,0 +1,271 @@
+use std::{any::type_name, io::Cursor, marker::PhantomData};
+
+use bevy_asset::{
+    io::{Reader, Writer},
+    processor::{Process, ProcessContext, ProcessError},
+    saver::{AssetSaver, SavedAsset},
+    AssetLoader, AssetPath, AsyncWriteExt, LoadContext, RenderAssetUsages,
+};
+use bevy_image::{Image, IntoDynamicImageError, TextureAtlasLayout};
+use bevy_reflect::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::packed::{read_header, read_sheet, write_header, write_sheet, PackedKind, PackedSheet};
+
+use super::{
+    asset::StaticCursor,
+    serde_asset::{Deserializer, SerdeStaticCursorLoader, SerdeStaticCursorLoaderSettings},
+};
+
+/// Saves static cursors in the packed binary format.
+///
+/// The cursor's image must be an `image` labeled asset, e.g. one loaded by
+/// [`SerdeStaticCursorLoader`] with `embed_image` set, so that its pixels are
+/// available.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct PackedStaticCursorSaver;
+
+/// Possible errors that can be produced by [`PackedStaticCursorSaver`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum PackedStaticCursorSaverError {
+    /// An [IO](std::io) error.
+    #[error("could not save asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("missing labeled asset: {0}")]
+    MissingLabeledAsset(&'static str),
+    /// An [IntoDynamicImageError] error.
+    #[error("could not convert image: {0}")]
+    IntoDynamicImageError(#[from] IntoDynamicImageError),
+}
+
+impl AssetSaver for PackedStaticCursorSaver {
+    type Asset = StaticCursor;
+    type Settings = ();
+    type OutputLoader = PackedStaticCursorLoader;
+    type Error = PackedStaticCursorSaverError;
+
+    async fn save(
+        &self,
+        writer: &mut Writer,
+        asset: SavedAsset<'_, '_, Self::Asset>,
+        _settings: &Self::Settings,
+        _asset_path: AssetPath<'_>,
+    ) -> Result<PackedStaticCursorLoaderSettings, Self::Error> {
+        let image = asset
+            .get_labeled::<Image>("image")
+            .ok_or(PackedStaticCursorSaverError::MissingLabeledAsset("image"))?;
+        let texture_atlas_layout = asset
+            .get_labeled::<TextureAtlasLayout>("texture_atlas_layout")
+            .ok_or(PackedStaticCursorSaverError::MissingLabeledAsset(
+                "texture_atlas_layout",
+            ))?;
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, PackedKind::Static)?;
+        write_sheet(
+            &mut bytes,
+            &PackedSheet {
+                image: image.get().clone().try_into_dynamic()?,
+                texture_atlas_layout: texture_atlas_layout.get().clone(),
+                hotspots: asset.hotspots.clone(),
+                variants: asset.variants.clone(),
+            },
+        )?;
+
+        writer.write_all(&bytes).await?;
+
+        Ok(PackedStaticCursorLoaderSettings::default())
+    }
+}
+
+/// A loader for static cursor assets in the packed binary format.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct PackedStaticCursorLoader;
+
+/// Settings for loading static cursors with [`PackedStaticCursorLoader`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PackedStaticCursorLoaderSettings {
+    /// The usage of the image.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+}
+
+/// Possible errors that can be produced by [`PackedStaticCursorLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum PackedStaticCursorLoaderError {
+    /// An [IO](std::io) error.
+    #[error("could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl AssetLoader for PackedStaticCursorLoader {
+    type Asset = StaticCursor;
+    type Settings = PackedStaticCursorLoaderSettings;
+    type Error = PackedStaticCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let mut reader = Cursor::new(bytes);
+        read_header(&mut reader, PackedKind::Static)?;
+        let sheet = read_sheet(&mut reader)?;
+
+        let image = Image::from_dynamic(sheet.image, true, settings.asset_usage);
+
+        let image = load_context.add_labeled_asset("image".to_string(), image);
+        let texture_atlas_layout = load_context.add_labeled_asset(
+            "texture_atlas_layout".to_string(),
+            sheet.texture_atlas_layout,
+        );
+
+        Ok(StaticCursor {
+            image,
+            texture_atlas_layout,
+            hotspots: sheet.hotspots,
+            variants: sheet.variants,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cur.packed"]
+    }
+}
+
+/// Processes text static cursors into the packed binary format.
+///
+/// The cursor is loaded with [`SerdeStaticCursorLoader`], so its image's
+/// color key and flips are applied and its atlas is laid out once, when
+/// processing, and then saved with [`PackedStaticCursorSaver`].
+#[derive(TypePath)]
+pub struct PackedStaticCursorProcessor<D: Deserializer + TypePath> {
+    _phantom: PhantomData<D>,
+}
+
+impl<D: Deserializer + TypePath> Default for PackedStaticCursorProcessor<D> {
+    fn default() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<D: Deserializer + TypePath> Process for PackedStaticCursorProcessor<D> {
+    type Settings = ();
+    type OutputLoader = PackedStaticCursorLoader;
+
+    async fn process(
+        &self,
+        context: &mut ProcessContext<'_>,
+        _settings: &Self::Settings,
+        writer: &mut Writer,
+    ) -> Result<PackedStaticCursorLoaderSettings, ProcessError> {
+        let loaded = context
+            .load_source_asset::<SerdeStaticCursorLoader<D>>(&SerdeStaticCursorLoaderSettings {
+                embed_image: true,
+            })
+            .await?;
+
+        // The source asset was loaded by a static cursor loader, so it is
+        // always a static cursor.
+        let asset = SavedAsset::<StaticCursor>::from_loaded(&loaded).ok_or_else(|| {
+            ProcessError::AssetTransformError(
+                format!(
+                    "expected a source asset of type {}",
+                    type_name::<StaticCursor>()
+                )
+                .into(),
+            )
+        })?;
+
+        PackedStaticCursorSaver
+            .save(writer, asset, &(), context.path().clone())
+            .await
+            .map_err(|error| ProcessError::AssetSaveError(error.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bevy_asset::prelude::*;
+
+    use crate::{
+        cur::asset::StaticCursorLoaderSettings,
+        testing::{create_app, cur_bytes, load_with_settings, save_with_labeled_atlas},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset(Path::new("cursor.cur"), cur_bytes(&[16, 32]));
+
+        let handle = load_with_settings::<StaticCursor, StaticCursorLoaderSettings>(
+            &mut app,
+            "cursor.cur",
+            |_| {},
+        )
+        .unwrap();
+        let cursor = app
+            .world()
+            .resource::<Assets<StaticCursor>>()
+            .get(&handle)
+            .unwrap()
+            .clone();
+
+        let bytes = save_with_labeled_atlas(
+            &app,
+            &PackedStaticCursorSaver,
+            &cursor,
+            &cursor.image,
+            &cursor.texture_atlas_layout,
+        )
+        .unwrap();
+        dir.insert_asset(Path::new("cursor.cur.packed"), bytes);
+
+        let handle = load_with_settings::<StaticCursor, PackedStaticCursorLoaderSettings>(
+            &mut app,
+            "cursor.cur.packed",
+            |_| {},
+        )
+        .unwrap();
+        let world = app.world();
+        let packed = world
+            .resource::<Assets<StaticCursor>>()
+            .get(&handle)
+            .unwrap();
+
+        assert_eq!(packed.variants, cursor.variants);
+        for index in 0..2 {
+            assert_eq!(
+                packed.hotspot_or_default(index),
+                cursor.hotspot_or_default(index)
+            );
+        }
+
+        let layouts = world.resource::<Assets<TextureAtlasLayout>>();
+        assert_eq!(
+            layouts.get(&packed.texture_atlas_layout),
+            layouts.get(&cursor.texture_atlas_layout)
+        );
+        let images = world.resource::<Assets<Image>>();
+        assert_eq!(
+            images.get(&packed.image).unwrap().data,
+            images.get(&cursor.image).unwrap().data
+        );
+    }
+}
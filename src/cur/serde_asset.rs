@@ -7,15 +7,22 @@ use bevy_asset::{io::Reader, prelude::*, AssetLoader, LoadContext};
 use bevy_image::TextureAtlasLayout;
 use bevy_math::UVec2;
 use bevy_reflect::prelude::*;
+use image::{DynamicImage, RgbaImage};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     asset_image::{load_image, LoadImageError},
+    atlas_grid::{hotspots_from_tiles, pack_tiles, process_sheet, Grid},
     hotspot::CursorHotspots,
 };
 
-use super::asset::StaticCursor;
+#[cfg(feature = "packed_asset")]
+use super::packed_asset::PackedStaticCursorProcessor;
+use super::{
+    asset::StaticCursor, decoder::decode_entry, encoder::EncodeError,
+    StaticCursor as DecodedStaticCursor,
+};
 
 /// A plugin for loading static cursor assets using Serde.
 pub struct SerdeStaticCursorAssetPlugin<D: Deserializer> {
@@ -40,6 +47,16 @@ impl<D: Deserializer + TypePath> Plugin for SerdeStaticCursorAssetPlugin<D> {
             D::default(),
             self.extensions.clone(),
         ));
+
+        // Pack the cursors when processing assets, so that release builds load
+        // them without any further image processing.
+        #[cfg(feature = "packed_asset")]
+        {
+            app.register_asset_processor(PackedStaticCursorProcessor::<D>::default());
+            for extension in &self.extensions {
+                app.set_default_asset_processor::<PackedStaticCursorProcessor<D>>(extension);
+            }
+        }
     }
 }
 
@@ -87,6 +104,113 @@ pub struct SerdeTextureAtlasLayout {
     pub offset: Option<UVec2>,
 }
 
+impl SerdeTextureAtlasLayout {
+    /// Returns the grid of tiles that the layout slices a sprite sheet into.
+    pub(crate) fn grid(&self) -> Grid {
+        Grid {
+            tile_size: self.tile_size,
+            columns: self.columns,
+            rows: self.rows,
+            padding: self.padding.unwrap_or_default(),
+            offset: self.offset.unwrap_or_default(),
+        }
+    }
+
+    /// Returns the layout of `grid`.
+    pub(crate) fn from_grid(grid: &Grid) -> Self {
+        Self {
+            tile_size: grid.tile_size,
+            columns: grid.columns,
+            rows: grid.rows,
+            padding: (grid.padding != UVec2::ZERO).then_some(grid.padding),
+            offset: (grid.offset != UVec2::ZERO).then_some(grid.offset),
+        }
+    }
+}
+
+/// Possible errors that can be produced when converting between a
+/// [`SerdeStaticCursor`] and a .CUR file.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SerdeStaticCursorConvertError {
+    /// An [IO](std::io) error.
+    #[error("could not decode cursor image: {0}")]
+    Io(#[from] std::io::Error),
+    /// An [EncodeError] error.
+    #[error("could not encode static cursor: {0}")]
+    EncodeError(#[from] EncodeError),
+    #[error("tile {0} is outside of the sprite sheet")]
+    TileOutOfBounds(usize),
+}
+
+impl SerdeStaticCursor {
+    /// Converts tiles of the cursor's sprite sheet to a .CUR file, with one
+    /// entry per tile.
+    ///
+    /// `image` is the sprite sheet at `self.image.path`. Its color key and
+    /// flips are applied before it is sliced by the texture atlas layout.
+    /// The tiles at `indices` are usually the same cursor at different sizes.
+    pub fn to_cur(
+        &self,
+        image: &DynamicImage,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Result<DecodedStaticCursor, SerdeStaticCursorConvertError> {
+        let sheet = process_sheet(
+            image,
+            self.image.color_key,
+            self.image.flip_x,
+            self.image.flip_y,
+        );
+        let grid = self.texture_atlas_layout.grid();
+
+        let tiles = indices
+            .into_iter()
+            .map(|i| {
+                grid.tile(&sheet, i)
+                    .map(|tile| (tile, self.hotspots.get_or_default(i)))
+                    .ok_or(SerdeStaticCursorConvertError::TileOutOfBounds(i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DecodedStaticCursor::from_images(
+            tiles.iter().map(|(tile, hotspot)| (tile, *hotspot)),
+        )?)
+    }
+
+    /// Converts a .CUR file to a text cursor and its sprite sheet.
+    ///
+    /// Each entry becomes a tile in a single row, in order. The returned
+    /// cursor refers to the sprite sheet by `image_path`, so save the sprite
+    /// sheet there, e.g. as a PNG.
+    pub fn from_cur(
+        cursor: &DecodedStaticCursor,
+        image_path: impl Into<String>,
+    ) -> Result<(Self, RgbaImage), SerdeStaticCursorConvertError> {
+        let (tiles, hotspots): (Vec<_>, Vec<_>) = cursor
+            .0
+            .entries()
+            .iter()
+            .map(decode_entry)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        let (sheet, grid) = pack_tiles(&tiles);
+
+        Ok((
+            Self {
+                image: SerdeImage {
+                    path: image_path.into(),
+                    ..Default::default()
+                },
+                texture_atlas_layout: SerdeTextureAtlasLayout::from_grid(&grid),
+                hotspots: hotspots_from_tiles(&hotspots),
+            },
+            sheet,
+        ))
+    }
+}
+
 /// Possible errors that can be produced by deserialization.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -160,6 +284,18 @@ pub struct SerdeStaticCursorLoader<D: Deserializer + TypePath> {
     deserializer: D,
 }
 
+/// Settings for loading static cursors with [`SerdeStaticCursorLoader`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SerdeStaticCursorLoaderSettings {
+    /// Whether to always load the image as an `image` labeled asset, even if
+    /// it has no color key and isn't flipped.
+    ///
+    /// This makes the image's pixels available to asset savers, e.g. when
+    /// packing the cursor during asset processing.
+    pub embed_image: bool,
+}
+
 /// Possible errors that can be produced by [`SerdeStaticCursorLoader`].
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -177,12 +313,12 @@ pub enum SerdeStaticCursorLoaderError {
 
 impl<D: Deserializer + TypePath> AssetLoader for SerdeStaticCursorLoader<D> {
     type Asset = StaticCursor;
-    type Settings = ();
+    type Settings = SerdeStaticCursorLoaderSettings;
     type Error = SerdeStaticCursorLoaderError;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
@@ -190,10 +326,14 @@ impl<D: Deserializer + TypePath> AssetLoader for SerdeStaticCursorLoader<D> {
 
         let c = self.deserializer.deserialize(&bytes)?;
 
-        // Load the image asset. If the image has a color key or needs to be
-        // flipped, load it as a dynamic image so we can process it now.
-        // Otherwise, load it as a regular asset.
-        let image = if c.image.color_key.is_some() || c.image.flip_x || c.image.flip_y {
+        // Load the image asset. If the image has a color key, needs to be
+        // flipped or should be embedded, load it as a dynamic image so we can
+        // process it now. Otherwise, load it as a regular asset.
+        let image = if settings.embed_image
+            || c.image.color_key.is_some()
+            || c.image.flip_x
+            || c.image.flip_y
+        {
             let image = load_image(
                 load_context,
                 &c.image.path,
@@ -226,6 +366,7 @@ impl<D: Deserializer + TypePath> AssetLoader for SerdeStaticCursorLoader<D> {
             image,
             texture_atlas_layout,
             hotspots: c.hotspots,
+            variants: Vec::new(),
         })
     }
 
@@ -243,3 +384,81 @@ impl<D: Deserializer + TypePath> SerdeStaticCursorLoader<D> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_cur_round_trip() {
+        // A 3x2 grid of 8x8 tiles with 1 pixel of padding, offset by 2 pixels.
+        let sheet = RgbaImage::from_fn(29, 21, |x, y| {
+            let column = x.saturating_sub(2) / 9;
+            let row = y.saturating_sub(2) / 9;
+            image::Rgba([(row * 3 + column) as u8 * 20, x as u8, y as u8, 255])
+        });
+
+        let cursor = SerdeStaticCursor {
+            image: SerdeImage {
+                path: "sheet.png".to_string(),
+                ..Default::default()
+            },
+            texture_atlas_layout: SerdeTextureAtlasLayout {
+                tile_size: UVec2::splat(8),
+                columns: 3,
+                rows: 2,
+                padding: Some(UVec2::splat(1)),
+                offset: Some(UVec2::splat(2)),
+            },
+            hotspots: CursorHotspots {
+                default: (1, 1),
+                overrides: HashMap::from([(4, (5, 6))]),
+            },
+        };
+
+        let cur = cursor
+            .to_cur(&DynamicImage::ImageRgba8(sheet.clone()), [4, 1])
+            .unwrap();
+        let entries = cur
+            .0
+            .entries()
+            .iter()
+            .map(|entry| decode_entry(entry).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].1, (5, 6));
+        assert_eq!(entries[0].0.get_pixel(0, 0).0, [80, 11, 11, 255]);
+        assert_eq!(entries[1].1, (1, 1));
+        assert_eq!(entries[1].0.get_pixel(0, 0).0, [20, 11, 2, 255]);
+
+        // The entries become the tiles of a single row, in order.
+        let (converted, converted_sheet) =
+            SerdeStaticCursor::from_cur(&cur, "converted.png").unwrap();
+        assert_eq!(converted.image.path, "converted.png");
+        assert_eq!(converted.texture_atlas_layout.tile_size, UVec2::splat(8));
+        assert_eq!(converted.texture_atlas_layout.columns, 2);
+        assert_eq!(converted.texture_atlas_layout.rows, 1);
+        assert_eq!(converted.texture_atlas_layout.padding, None);
+        assert_eq!(converted.texture_atlas_layout.offset, None);
+        assert_eq!(converted_sheet.dimensions(), (16, 8));
+        assert_eq!(converted.hotspots.get_or_default(0), (5, 6));
+        assert_eq!(converted.hotspots.get_or_default(1), (1, 1));
+
+        // Converting back gives the same entries.
+        let reconverted = converted
+            .to_cur(&DynamicImage::ImageRgba8(converted_sheet), [0, 1])
+            .unwrap();
+        for (entry, (image, hotspot)) in reconverted.0.entries().iter().zip(&entries) {
+            let (reconverted_image, reconverted_hotspot) = decode_entry(entry).unwrap();
+            assert_eq!(reconverted_image, *image);
+            assert_eq!(reconverted_hotspot, *hotspot);
+        }
+
+        assert!(matches!(
+            cursor.to_cur(&DynamicImage::ImageRgba8(sheet), [6]),
+            Err(SerdeStaticCursorConvertError::TileOutOfBounds(6))
+        ));
+    }
+}
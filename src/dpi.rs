@@ -0,0 +1,274 @@
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_window::{CursorIcon, CustomCursor, Window};
+
+use crate::{
+    ani::asset::AnimatedCursor,
+    cur::asset::StaticCursor,
+    handle::CursorHandle,
+    variant::{best_variant, size_for_scale_factor},
+};
+
+/// A plugin that switches the custom cursors of windows with a
+/// [`DpiAwareCursor`] to the variant that best suits the window's scale factor.
+pub struct DpiAwareCursorPlugin;
+
+impl Plugin for DpiAwareCursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DpiAwareCursor>()
+            .add_systems(Update, select_cursor_variants);
+    }
+}
+
+/// Selects the resolution variant of a window's custom cursor that best suits
+/// the window's scale factor.
+///
+/// Insert this on a [`Window`] entity whose [`CursorIcon::Custom`] was built
+/// from `cursor`, e.g. with
+/// [`CustomCursorImageBuilder`](crate::prelude::CustomCursorImageBuilder).
+/// The cursor is assumed to start at its first variant. Whenever the window's
+/// scale factor changes, the cursor's atlas index and hotspot are switched to
+/// the best variant while keeping the current frame.
+///
+/// Only cursors with variants, such as multi-resolution .CUR and .ANI files,
+/// are affected.
+#[derive(Clone, Component, Debug, Reflect)]
+#[reflect(Component, Debug)]
+pub struct DpiAwareCursor {
+    /// The cursor whose variants are selected from.
+    pub cursor: CursorHandle,
+    /// The atlas offset of the selected variant.
+    atlas_offset: usize,
+    /// The scale factor that the variant was selected for.
+    scale_factor: Option<f32>,
+}
+
+impl DpiAwareCursor {
+    /// Creates a new [`DpiAwareCursor`] for the given cursor.
+    pub fn new(cursor: impl Into<CursorHandle>) -> Self {
+        Self {
+            cursor: cursor.into(),
+            atlas_offset: 0,
+            scale_factor: None,
+        }
+    }
+
+    /// Returns the atlas offset of the selected variant.
+    ///
+    /// Add a frame index to this to get the frame's atlas index in the
+    /// selected variant.
+    #[inline(always)]
+    pub fn atlas_offset(&self) -> usize {
+        self.atlas_offset
+    }
+}
+
+fn select_cursor_variants(
+    static_cursors: Res<Assets<StaticCursor>>,
+    animated_cursors: Res<Assets<AnimatedCursor>>,
+    mut query: Query<(&Window, &mut DpiAwareCursor, Option<&mut CursorIcon>)>,
+) {
+    for (window, mut dpi_aware_cursor, cursor_icon) in &mut query {
+        let scale_factor = window.scale_factor();
+        if dpi_aware_cursor.scale_factor == Some(scale_factor) {
+            continue;
+        }
+
+        let (variants, hotspots) = match &dpi_aware_cursor.cursor {
+            CursorHandle::Static(handle) => {
+                let Some(c) = static_cursors.get(handle) else {
+                    continue;
+                };
+                (&c.variants, &c.hotspots)
+            }
+            CursorHandle::Animated(handle) => {
+                let Some(c) = animated_cursors.get(handle) else {
+                    continue;
+                };
+                (&c.variants, &c.hotspots)
+            }
+        };
+
+        let Some(variant) = best_variant(variants, size_for_scale_factor(scale_factor)) else {
+            dpi_aware_cursor.scale_factor = Some(scale_factor);
+            continue;
+        };
+
+        let old_offset = dpi_aware_cursor.atlas_offset;
+        if variant.atlas_offset == old_offset {
+            dpi_aware_cursor.scale_factor = Some(scale_factor);
+            continue;
+        }
+
+        // The variant is only selected once the window's icon has been
+        // switched to it, so that the atlas offset always matches the icon.
+        // Until the window has a custom cursor image, this is retried.
+        let Some(mut cursor_icon) = cursor_icon else {
+            continue;
+        };
+        let CursorIcon::Custom(CustomCursor::Image(ref mut image)) = *cursor_icon else {
+            continue;
+        };
+        let Some(atlas) = image.texture_atlas.as_mut() else {
+            continue;
+        };
+
+        // Keep the current frame, e.g. of an animation, in the new variant.
+        let frame = atlas.index.saturating_sub(old_offset);
+        atlas.index = variant.atlas_offset + frame;
+
+        image.hotspot = hotspots.get_or_default(atlas.index);
+
+        dpi_aware_cursor.atlas_offset = variant.atlas_offset;
+        dpi_aware_cursor.scale_factor = Some(scale_factor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        ani::asset::AnimatedCursorLoaderSettings,
+        builder::CustomCursorImageBuilder,
+        testing::{ani_bytes, create_app, load_with_settings},
+    };
+
+    use super::*;
+
+    fn atlas_index_and_hotspot(world: &World, entity: Entity) -> (usize, (u16, u16)) {
+        let Some(CursorIcon::Custom(CustomCursor::Image(image))) = world.get::<CursorIcon>(entity)
+        else {
+            panic!("expected a custom cursor image");
+        };
+        (image.texture_atlas.as_ref().unwrap().index, image.hotspot)
+    }
+
+    #[test]
+    fn test_select_cursor_variants() {
+        let (mut app, dir) = create_app();
+        app.add_plugins(DpiAwareCursorPlugin);
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(2, &[32, 64], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |_| {},
+        )
+        .unwrap();
+
+        let cursor = app
+            .world()
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        assert_eq!(
+            cursor
+                .variants
+                .iter()
+                .map(|v| (v.size.x, v.atlas_offset))
+                .collect::<Vec<_>>(),
+            vec![(32, 0), (64, 2)]
+        );
+
+        // The second frame of the 32x32 variant.
+        let cursor_icon = CursorIcon::Custom(
+            CustomCursorImageBuilder::from_animated_cursor(cursor, Some(1)).build(),
+        );
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), cursor_icon, DpiAwareCursor::new(handle)))
+            .id();
+
+        app.update();
+        assert_eq!(atlas_index_and_hotspot(app.world(), window), (1, (16, 16)));
+
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .resolution
+            .set_scale_factor(2.0);
+        app.update();
+
+        // The second frame of the 64x64 variant.
+        assert_eq!(atlas_index_and_hotspot(app.world(), window), (3, (32, 32)));
+        assert_eq!(
+            app.world()
+                .get::<DpiAwareCursor>(window)
+                .unwrap()
+                .atlas_offset(),
+            2
+        );
+
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .resolution
+            .set_scale_factor(1.0);
+        app.update();
+
+        assert_eq!(atlas_index_and_hotspot(app.world(), window), (1, (16, 16)));
+        assert_eq!(
+            app.world()
+                .get::<DpiAwareCursor>(window)
+                .unwrap()
+                .atlas_offset(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_select_cursor_variant_before_icon() {
+        let (mut app, dir) = create_app();
+        app.add_plugins(DpiAwareCursorPlugin);
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(2, &[32, 64], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |_| {},
+        )
+        .unwrap();
+
+        let mut window = Window::default();
+        window.resolution.set_scale_factor(2.0);
+        let window = app
+            .world_mut()
+            .spawn((window, DpiAwareCursor::new(handle.clone())))
+            .id();
+
+        // Without an icon to switch, the first variant stays selected.
+        app.update();
+        assert_eq!(
+            app.world()
+                .get::<DpiAwareCursor>(window)
+                .unwrap()
+                .atlas_offset(),
+            0
+        );
+
+        // The second frame of the 32x32 variant.
+        let cursor = app
+            .world()
+            .resource::<Assets<AnimatedCursor>>()
+            .get(&handle)
+            .unwrap();
+        let cursor_icon = CursorIcon::Custom(
+            CustomCursorImageBuilder::from_animated_cursor(cursor, Some(1)).build(),
+        );
+        app.world_mut().entity_mut(window).insert(cursor_icon);
+        app.update();
+
+        // The second frame of the 64x64 variant.
+        assert_eq!(atlas_index_and_hotspot(app.world(), window), (3, (32, 32)));
+        assert_eq!(
+            app.world()
+                .get::<DpiAwareCursor>(window)
+                .unwrap()
+                .atlas_offset(),
+            2
+        );
+    }
+}
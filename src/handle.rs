@@ -0,0 +1,24 @@
+use bevy_asset::Handle;
+use bevy_reflect::prelude::*;
+
+use crate::{ani::asset::AnimatedCursor, cur::asset::StaticCursor};
+
+/// A handle to either a static or an animated cursor asset.
+#[derive(Clone, Debug, Eq, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub enum CursorHandle {
+    Static(Handle<StaticCursor>),
+    Animated(Handle<AnimatedCursor>),
+}
+
+impl From<Handle<StaticCursor>> for CursorHandle {
+    fn from(handle: Handle<StaticCursor>) -> Self {
+        CursorHandle::Static(handle)
+    }
+}
+
+impl From<Handle<AnimatedCursor>> for CursorHandle {
+    fn from(handle: Handle<AnimatedCursor>) -> Self {
+        CursorHandle::Animated(handle)
+    }
+}
@@ -1,19 +1,59 @@
 use bevy_app::prelude::*;
 
-use crate::{ani::asset::AnimatedCursorAssetPlugin, cur::asset::StaticCursorAssetPlugin};
+use crate::{
+    ani::asset::AnimatedCursorAssetPlugin, cur::asset::StaticCursorAssetPlugin,
+    theme::asset::CursorThemeAssetPlugin, xcursor::asset::XCursorAssetPlugin,
+};
 
 pub mod ani;
+#[cfg(feature = "animated_image")]
+pub mod animated_image;
 mod asset_image;
+mod atlas;
+#[cfg(feature = "serde_asset")]
+mod atlas_grid;
 mod builder;
 pub mod cur;
+pub mod dpi;
+pub mod handle;
 pub mod hotspot;
+#[cfg(feature = "packed_asset")]
+mod packed;
+pub mod player;
+#[cfg(feature = "bevy_spritesheet_animation")]
+pub mod spritesheet;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(test)]
+mod testing;
+pub mod theme;
+pub mod variant;
+pub mod xcursor;
 
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        ani::asset::AnimatedCursor, builder::CustomCursorImageBuilder, cur::asset::StaticCursor,
-        hotspot::CursorHotspots, CursorAssetPlugin,
+        ani::asset::AnimatedCursor,
+        builder::CustomCursorImageBuilder,
+        cur::asset::StaticCursor,
+        dpi::{DpiAwareCursor, DpiAwareCursorPlugin},
+        handle::CursorHandle,
+        hotspot::CursorHotspots,
+        player::{
+            AnimatedCursorPlayback, AnimatedCursorPlayer, AnimatedCursorPlayerPlugin,
+            CursorAnimationFinished, CursorAnimationLooped, CursorClipFinished, CursorClipStarted,
+            CursorMarkerHit,
+        },
+        theme::{
+            asset::CursorTheme,
+            system::{SystemCursorTheme, SystemCursorThemePlugin},
+        },
+        CursorAssetPlugin,
     };
+
+    #[cfg(feature = "bevy_spritesheet_animation")]
+    #[doc(hidden)]
+    pub use crate::spritesheet::{cursor_sprite, SpritesheetCursor, SpritesheetCursorPlugin};
 }
 
 pub struct CursorAssetPlugin;
@@ -26,5 +66,19 @@ impl Plugin for CursorAssetPlugin {
         if !app.is_plugin_added::<AnimatedCursorAssetPlugin>() {
             app.add_plugins(AnimatedCursorAssetPlugin);
         }
+        if !app.is_plugin_added::<XCursorAssetPlugin>() {
+            app.add_plugins(XCursorAssetPlugin);
+        }
+        if !app.is_plugin_added::<CursorThemeAssetPlugin>() {
+            app.add_plugins(CursorThemeAssetPlugin);
+        }
+        #[cfg(feature = "animated_image")]
+        if !app.is_plugin_added::<animated_image::AnimatedImageCursorAssetPlugin>() {
+            app.add_plugins(animated_image::AnimatedImageCursorAssetPlugin);
+        }
+        #[cfg(feature = "svg")]
+        if !app.is_plugin_added::<svg::asset::SvgCursorAssetPlugin>() {
+            app.add_plugins(svg::asset::SvgCursorAssetPlugin);
+        }
     }
 }
@@ -0,0 +1,274 @@
+//! The binary format shared by packed static and animated cursors.
+//!
+//! A packed cursor starts with a header:
+//!
+//! - The magic bytes `CKPK`.
+//! - The format version as a `u16`.
+//! - The kind of cursor as a `u8`, `0` for static and `1` for animated.
+//!
+//! It's followed by the sprite sheet:
+//!
+//! - The length of the image as a `u32`, followed by the image as a PNG.
+//! - The size of the texture atlas layout as two `u32`s, the number of
+//!   textures as a `u32` and, for each texture, its `min` and `max` as four
+//!   `u32`s.
+//! - The default hotspot as two `u16`s, the number of overrides as a `u32`
+//!   and, for each override, its index as a `u32` and hotspot as two `u16`s.
+//! - The number of variants as a `u32` and, for each variant, its size as two
+//!   `u32`s and its atlas offset as a `u32`.
+//!
+//! Animated cursors then contain their animations. All values are little
+//! endian.
+
+use std::io::{self, Cursor, Read, Write};
+
+use bevy_image::TextureAtlasLayout;
+use bevy_math::{URect, UVec2};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use image::{DynamicImage, ImageFormat};
+
+use crate::{hotspot::CursorHotspots, variant::CursorVariant};
+
+const MAGIC: &[u8; 4] = b"CKPK";
+
+const VERSION: u16 = 1;
+
+/// The kind of cursor that a packed cursor contains.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum PackedKind {
+    Static = 0,
+    Animated = 1,
+}
+
+/// The sprite sheet of a packed cursor.
+pub(crate) struct PackedSheet {
+    pub image: DynamicImage,
+    pub texture_atlas_layout: TextureAtlasLayout,
+    pub hotspots: CursorHotspots,
+    pub variants: Vec<CursorVariant>,
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Reads `len` bytes.
+///
+/// Lengths are read from the file, so the bytes are read incrementally rather
+/// than allocated up front, and a corrupt length fails once the data runs out.
+fn read_bytes(reader: &mut impl Read, len: u32) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn write_header(writer: &mut impl Write, kind: PackedKind) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_u16::<LittleEndian>(VERSION)?;
+    writer.write_u8(kind as u8)
+}
+
+/// Reads the header and checks that the packed cursor is of the given kind.
+pub(crate) fn read_header(reader: &mut impl Read, kind: PackedKind) -> io::Result<()> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a packed cursor"));
+    }
+
+    let version = reader.read_u16::<LittleEndian>()?;
+    if version != VERSION {
+        return Err(invalid_data(format!(
+            "unsupported packed cursor version: {}",
+            version
+        )));
+    }
+
+    if reader.read_u8()? != kind as u8 {
+        return Err(invalid_data(format!("expected a packed {:?} cursor", kind)));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn write_sheet(writer: &mut impl Write, sheet: &PackedSheet) -> io::Result<()> {
+    let mut png = Cursor::new(Vec::new());
+    sheet
+        .image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(io::Error::other)?;
+    let png = png.into_inner();
+    writer.write_u32::<LittleEndian>(png.len() as u32)?;
+    writer.write_all(&png)?;
+
+    let layout = &sheet.texture_atlas_layout;
+    writer.write_u32::<LittleEndian>(layout.size.x)?;
+    writer.write_u32::<LittleEndian>(layout.size.y)?;
+    writer.write_u32::<LittleEndian>(layout.textures.len() as u32)?;
+    for rect in &layout.textures {
+        for value in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+            writer.write_u32::<LittleEndian>(value)?;
+        }
+    }
+
+    // Sort the overrides so that packing the same cursor twice gives the same
+    // bytes.
+    let mut overrides = sheet.hotspots.overrides.iter().collect::<Vec<_>>();
+    overrides.sort_unstable();
+
+    writer.write_u16::<LittleEndian>(sheet.hotspots.default.0)?;
+    writer.write_u16::<LittleEndian>(sheet.hotspots.default.1)?;
+    writer.write_u32::<LittleEndian>(overrides.len() as u32)?;
+    for (&index, &(x, y)) in overrides {
+        writer.write_u32::<LittleEndian>(index as u32)?;
+        writer.write_u16::<LittleEndian>(x)?;
+        writer.write_u16::<LittleEndian>(y)?;
+    }
+
+    writer.write_u32::<LittleEndian>(sheet.variants.len() as u32)?;
+    for variant in &sheet.variants {
+        writer.write_u32::<LittleEndian>(variant.size.x)?;
+        writer.write_u32::<LittleEndian>(variant.size.y)?;
+        writer.write_u32::<LittleEndian>(variant.atlas_offset as u32)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read_sheet(reader: &mut impl Read) -> io::Result<PackedSheet> {
+    let png_len = reader.read_u32::<LittleEndian>()?;
+    let png = read_bytes(reader, png_len)?;
+    let image = image::load_from_memory_with_format(&png, ImageFormat::Png)
+        .map_err(|e| invalid_data(e.to_string()))?;
+
+    let size = UVec2::new(
+        reader.read_u32::<LittleEndian>()?,
+        reader.read_u32::<LittleEndian>()?,
+    );
+    let textures = (0..reader.read_u32::<LittleEndian>()?)
+        .map(|_| {
+            Ok(URect::new(
+                reader.read_u32::<LittleEndian>()?,
+                reader.read_u32::<LittleEndian>()?,
+                reader.read_u32::<LittleEndian>()?,
+                reader.read_u32::<LittleEndian>()?,
+            ))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let default = (
+        reader.read_u16::<LittleEndian>()?,
+        reader.read_u16::<LittleEndian>()?,
+    );
+    let overrides = (0..reader.read_u32::<LittleEndian>()?)
+        .map(|_| {
+            Ok((
+                reader.read_u32::<LittleEndian>()? as usize,
+                (
+                    reader.read_u16::<LittleEndian>()?,
+                    reader.read_u16::<LittleEndian>()?,
+                ),
+            ))
+        })
+        .collect::<io::Result<_>>()?;
+
+    let variants = (0..reader.read_u32::<LittleEndian>()?)
+        .map(|_| {
+            Ok(CursorVariant {
+                size: UVec2::new(
+                    reader.read_u32::<LittleEndian>()?,
+                    reader.read_u32::<LittleEndian>()?,
+                ),
+                atlas_offset: reader.read_u32::<LittleEndian>()? as usize,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok(PackedSheet {
+        image,
+        texture_atlas_layout: TextureAtlasLayout { size, textures },
+        hotspots: CursorHotspots { default, overrides },
+        variants,
+    })
+}
+
+pub(crate) fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(value.len() as u32)?;
+    writer.write_all(value.as_bytes())
+}
+
+pub(crate) fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let len = reader.read_u32::<LittleEndian>()?;
+    let bytes = read_bytes(reader, len)?;
+    String::from_utf8(bytes).map_err(|e| invalid_data(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use image::RgbaImage;
+
+    use super::*;
+
+    #[test]
+    fn test_sheet_round_trip() {
+        let image = RgbaImage::from_fn(16, 8, |x, y| {
+            image::Rgba([x as u8 * 16, y as u8 * 32, 0, 255])
+        });
+        let sheet = PackedSheet {
+            image: DynamicImage::ImageRgba8(image.clone()),
+            texture_atlas_layout: TextureAtlasLayout {
+                size: UVec2::new(16, 8),
+                textures: vec![URect::new(0, 0, 8, 8), URect::new(8, 0, 16, 8)],
+            },
+            hotspots: CursorHotspots {
+                default: (1, 2),
+                overrides: HashMap::from([(1, (3, 4))]),
+            },
+            variants: vec![CursorVariant {
+                size: UVec2::splat(8),
+                atlas_offset: 0,
+            }],
+        };
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, PackedKind::Static).unwrap();
+        write_sheet(&mut bytes, &sheet).unwrap();
+        write_string(&mut bytes, "busy").unwrap();
+
+        let mut reader = Cursor::new(bytes);
+        read_header(&mut reader, PackedKind::Static).unwrap();
+        let read = read_sheet(&mut reader).unwrap();
+
+        assert_eq!(read.image.to_rgba8(), image);
+        assert_eq!(
+            read.texture_atlas_layout.size,
+            sheet.texture_atlas_layout.size
+        );
+        assert_eq!(
+            read.texture_atlas_layout.textures,
+            sheet.texture_atlas_layout.textures
+        );
+        assert_eq!(read.hotspots.default, sheet.hotspots.default);
+        assert_eq!(read.hotspots.overrides, sheet.hotspots.overrides);
+        assert_eq!(read.variants, sheet.variants);
+        assert_eq!(read_string(&mut reader).unwrap(), "busy");
+
+        let mut reader = Cursor::new(b"CKPK\x01\x00\x01".to_vec());
+        assert!(read_header(&mut reader, PackedKind::Static).is_err());
+    }
+
+    #[test]
+    fn test_read_corrupt_length() {
+        // A length far larger than the data fails instead of allocating it.
+        let mut bytes = u32::MAX.to_le_bytes().to_vec();
+        bytes.extend_from_slice(b"busy");
+
+        let error = read_string(&mut Cursor::new(bytes)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,978 @@
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_time::{Real, Time, Virtual};
+use bevy_window::{CursorIcon, CustomCursor};
+
+use crate::{
+    ani::{
+        animation::{Animation, AnimationFrame, AnimationRepeat},
+        asset::AnimatedCursor,
+    },
+    builder::CustomCursorImageBuilder,
+    dpi::DpiAwareCursor,
+};
+
+/// A plugin that plays the animated cursors of windows with an
+/// [`AnimatedCursorPlayer`].
+pub struct AnimatedCursorPlayerPlugin;
+
+impl Plugin for AnimatedCursorPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AnimatedCursorPlayer>()
+            .register_type::<AnimatedCursorPlayback>()
+            .add_systems(Update, play_animated_cursors);
+    }
+}
+
+/// Plays an [`AnimatedCursor`] as the custom cursor of the [`Window`] entity
+/// that it is inserted on.
+///
+/// The player inserts a [`CursorIcon::Custom`] once the cursor has loaded and
+/// then updates its atlas index and hotspot as the cursor's animation plays.
+/// Other settings of the cursor icon, such as `flip_x`, are left alone.
+///
+/// If the window also has a [`DpiAwareCursor`], frames are taken from the
+/// variant that it selected.
+///
+/// Cursors with named animations play their default animation unless another
+/// one is selected with [`AnimatedCursorPlayer::play`].
+///
+/// When the cursor asset is modified, for example when it's hot reloaded, its
+/// animation plays again from the start.
+///
+/// Use the [`AnimatedCursorPlayback`] component, which is inserted along with
+/// the player, to pause, seek or change the speed of the animation.
+///
+/// As the animation plays, the player triggers [`CursorClipStarted`],
+/// [`CursorClipFinished`], [`CursorMarkerHit`], [`CursorAnimationLooped`] and
+/// [`CursorAnimationFinished`] events that target the window entity.
+///
+/// [`Window`]: bevy_window::Window
+#[derive(Clone, Component, Debug, Reflect)]
+#[reflect(Component, Debug)]
+#[require(AnimatedCursorPlayback)]
+pub struct AnimatedCursorPlayer {
+    /// The cursor to play.
+    pub cursor: Handle<AnimatedCursor>,
+    /// The name of the cursor's animation to play, or `None` to play its
+    /// default animation.
+    animation: Option<String>,
+    /// The cursor and animation that `frames` were resolved from.
+    playing: Option<(AssetId<AnimatedCursor>, Option<String>)>,
+    /// The frames of one repetition of the animation.
+    frames: Vec<AnimationFrame>,
+    /// The index of the current frame in `frames`.
+    step: usize,
+    /// The number of completed repetitions.
+    repetition: usize,
+    /// The time spent on the current frame.
+    elapsed: Duration,
+    /// Whether the animation has finished.
+    finished: bool,
+}
+
+impl AnimatedCursorPlayer {
+    /// Creates a new [`AnimatedCursorPlayer`] that plays the given cursor from
+    /// the start.
+    pub fn new(cursor: Handle<AnimatedCursor>) -> Self {
+        Self {
+            cursor,
+            animation: None,
+            playing: None,
+            frames: Vec::new(),
+            step: 0,
+            repetition: 0,
+            elapsed: Duration::ZERO,
+            finished: false,
+        }
+    }
+
+    /// Plays the cursor's animation with the given name from the start.
+    pub fn with_animation(mut self, name: impl Into<String>) -> Self {
+        self.animation = Some(name.into());
+        self
+    }
+
+    /// Switches to the cursor's animation with the given name and plays it from
+    /// the start. If the cursor has no animation with that name, its default
+    /// animation is played.
+    ///
+    /// Switching to the animation that is already playing does nothing.
+    pub fn play(&mut self, name: impl Into<String>) {
+        self.animation = Some(name.into());
+    }
+
+    /// Switches to the cursor's default animation and plays it from the start.
+    ///
+    /// Switching to the animation that is already playing does nothing.
+    pub fn play_default(&mut self) {
+        self.animation = None;
+    }
+
+    /// Returns the name of the animation that is selected, or `None` if the
+    /// default animation is selected.
+    pub fn animation(&self) -> Option<&str> {
+        self.animation.as_deref()
+    }
+
+    /// Returns whether the animation has finished.
+    ///
+    /// Looping animations never finish.
+    #[inline(always)]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the index of the current step in one repetition of the
+    /// animation.
+    #[inline(always)]
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the number of steps in one repetition of the animation, or `0`
+    /// if the cursor has not loaded yet.
+    #[inline(always)]
+    pub fn step_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the current frame, or `None` if the cursor has not loaded yet.
+    fn frame(&self) -> Option<&AnimationFrame> {
+        self.frames.get(self.step)
+    }
+
+    /// Pushes a [`PlayerEvent::FrameDisplayed`] for the current frame.
+    fn push_frame_displayed(&self, events: &mut Vec<PlayerEvent>) {
+        if let Some(frame) = self.frame() {
+            events.push(PlayerEvent::FrameDisplayed(
+                frame.clip_index,
+                frame.frame_index,
+            ));
+        }
+    }
+
+    /// Returns whether the selected cursor and animation are being played.
+    fn is_playing_selected(&self) -> bool {
+        self.playing
+            .as_ref()
+            .is_some_and(|(id, animation)| *id == self.cursor.id() && *animation == self.animation)
+    }
+
+    /// Starts playing the selected animation of `c` from its first frame.
+    fn start(&mut self, c: &AnimatedCursor, events: &mut Vec<PlayerEvent>) {
+        self.playing = Some((self.cursor.id(), self.animation.clone()));
+        self.frames = c.animation_or_default(self.animation.as_deref()).frames();
+        self.step = 0;
+        self.repetition = 0;
+        self.elapsed = Duration::ZERO;
+        // An animation without any duration can't advance, so it's displayed
+        // as a static cursor.
+        self.finished = self.frames.iter().all(|f| f.duration.is_zero());
+
+        if let Some(frame) = self.frame() {
+            events.push(PlayerEvent::ClipStarted(frame.clip_index));
+        }
+        self.push_frame_displayed(events);
+    }
+
+    /// Jumps to `step` in the current repetition. Steps past the end jump to
+    /// the last step.
+    fn seek(&mut self, step: usize, events: &mut Vec<PlayerEvent>) {
+        self.step = step.min(self.frames.len().saturating_sub(1));
+        self.elapsed = Duration::ZERO;
+        self.finished = self.frames.iter().all(|f| f.duration.is_zero());
+
+        self.push_frame_displayed(events);
+    }
+
+    /// Advances the animation by `delta`.
+    fn advance(&mut self, delta: Duration, repeat: AnimationRepeat, events: &mut Vec<PlayerEvent>) {
+        if self.finished {
+            return;
+        }
+
+        // A repetition without any duration can't advance, so its frame is
+        // held instead of looping forever.
+        if self.frames.iter().all(|frame| frame.duration.is_zero()) {
+            return;
+        }
+
+        self.elapsed += delta;
+
+        while let Some(&frame) = self.frame() {
+            if self.elapsed < frame.duration {
+                break;
+            }
+
+            self.elapsed -= frame.duration;
+            self.step += 1;
+
+            if let Some(next) = self.frame() {
+                if next.clip_index != frame.clip_index
+                    || next.clip_repetition != frame.clip_repetition
+                {
+                    events.push(PlayerEvent::ClipFinished(frame.clip_index));
+                    events.push(PlayerEvent::ClipStarted(next.clip_index));
+                }
+                self.push_frame_displayed(events);
+                continue;
+            }
+
+            self.repetition += 1;
+
+            events.push(PlayerEvent::ClipFinished(frame.clip_index));
+
+            match repeat {
+                AnimationRepeat::Times(times) if self.repetition >= times => {
+                    self.step = self.frames.len() - 1;
+                    self.elapsed = Duration::ZERO;
+                    self.finished = true;
+
+                    events.push(PlayerEvent::Finished);
+                    break;
+                }
+                _ => {
+                    self.step = 0;
+
+                    events.push(PlayerEvent::Looped(self.repetition));
+                    events.push(PlayerEvent::ClipStarted(self.frames[0].clip_index));
+                    self.push_frame_displayed(events);
+                }
+            }
+        }
+    }
+}
+
+/// Triggered when an [`AnimatedCursorPlayer`] starts playing a clip of its
+/// animation, including when the clip is played again because it repeats, is
+/// played backwards in a ping-pong or the animation loops.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct CursorClipStarted {
+    /// The window entity that the player is on.
+    pub entity: Entity,
+    /// The index of the clip in the animation.
+    pub clip_index: usize,
+}
+
+/// Triggered when an [`AnimatedCursorPlayer`] finishes playing a clip of its
+/// animation, once for each time the clip is played.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct CursorClipFinished {
+    /// The window entity that the player is on.
+    pub entity: Entity,
+    /// The index of the clip in the animation.
+    pub clip_index: usize,
+}
+
+/// Triggered when an [`AnimatedCursorPlayer`] reaches a frame of a clip that has
+/// an [`AnimationMarker`], once for each marker of the frame.
+///
+/// [`AnimationMarker`]: crate::ani::animation::AnimationMarker
+#[derive(Clone, Debug, EntityEvent)]
+pub struct CursorMarkerHit {
+    /// The window entity that the player is on.
+    pub entity: Entity,
+    /// The index of the clip in the animation.
+    pub clip_index: usize,
+    /// The name of the marker.
+    pub name: String,
+}
+
+/// Triggered when an [`AnimatedCursorPlayer`] completes a repetition of its
+/// animation and starts the next one.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct CursorAnimationLooped {
+    /// The window entity that the player is on.
+    pub entity: Entity,
+    /// The number of completed repetitions.
+    pub repetition: usize,
+}
+
+/// Triggered when an [`AnimatedCursorPlayer`] completes the last repetition of
+/// its animation. Looping animations never finish.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct CursorAnimationFinished {
+    /// The window entity that the player is on.
+    pub entity: Entity,
+}
+
+/// An event produced by a player, which is triggered once the player has been
+/// updated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PlayerEvent {
+    ClipStarted(usize),
+    ClipFinished(usize),
+    /// A frame was displayed, given by its clip index and its index in the
+    /// clip.
+    FrameDisplayed(usize, usize),
+    Looped(usize),
+    Finished,
+}
+
+impl PlayerEvent {
+    fn trigger(self, commands: &mut Commands, entity: Entity, animation: &Animation) {
+        match self {
+            PlayerEvent::ClipStarted(clip_index) => {
+                commands.trigger(CursorClipStarted { entity, clip_index });
+            }
+            PlayerEvent::ClipFinished(clip_index) => {
+                commands.trigger(CursorClipFinished { entity, clip_index });
+            }
+            PlayerEvent::FrameDisplayed(clip_index, frame_index) => {
+                let Some(clip) = animation.clips.get(clip_index) else {
+                    return;
+                };
+
+                for marker in clip.markers.iter().filter(|m| m.frame == frame_index) {
+                    commands.trigger(CursorMarkerHit {
+                        entity,
+                        clip_index,
+                        name: marker.name.clone(),
+                    });
+                }
+            }
+            PlayerEvent::Looped(repetition) => {
+                commands.trigger(CursorAnimationLooped { entity, repetition });
+            }
+            PlayerEvent::Finished => commands.trigger(CursorAnimationFinished { entity }),
+        }
+    }
+}
+
+/// The clock that drives the playback of an animated cursor.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Reflect)]
+#[reflect(Debug, Default, Hash, PartialEq)]
+pub enum PlaybackClock {
+    /// The default [`Time`], which follows [`Time<Virtual>`] in `Update`.
+    #[default]
+    Default,
+    /// [`Time<Virtual>`], which stops when the game is paused.
+    Virtual,
+    /// [`Time<Real>`], which keeps running when the game is paused.
+    Real,
+}
+
+/// A pending change to the playback position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+enum PlaybackSeek {
+    Step(usize),
+    Restart,
+}
+
+/// Controls the playback of an [`AnimatedCursorPlayer`].
+#[derive(Clone, Component, Debug, Reflect)]
+#[reflect(Component, Debug, Default)]
+pub struct AnimatedCursorPlayback {
+    /// Whether playback is paused.
+    paused: bool,
+    /// The speed multiplier. `1.0` plays the animation at its authored speed.
+    /// Negative values are treated as `0.0`.
+    pub speed: f32,
+    /// The clock that drives playback.
+    pub clock: PlaybackClock,
+    /// A seek that is applied the next time the player runs.
+    seek: Option<PlaybackSeek>,
+}
+
+impl Default for AnimatedCursorPlayback {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: 1.0,
+            clock: PlaybackClock::Default,
+            seek: None,
+        }
+    }
+}
+
+impl AnimatedCursorPlayback {
+    /// Pauses playback.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes playback.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Pauses playback if it's playing, or resumes it if it's paused.
+    pub fn toggle(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Returns whether playback is paused.
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Sets the speed multiplier.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Sets the clock that drives playback.
+    pub fn set_clock(&mut self, clock: PlaybackClock) {
+        self.clock = clock;
+    }
+
+    /// Jumps to `step` in the current repetition of the animation. See
+    /// [`AnimatedCursorPlayer::step`].
+    ///
+    /// A finished animation plays again from `step`.
+    pub fn seek(&mut self, step: usize) {
+        self.seek = Some(PlaybackSeek::Step(step));
+    }
+
+    /// Plays the animation again from its first repetition.
+    pub fn restart(&mut self) {
+        self.seek = Some(PlaybackSeek::Restart);
+    }
+}
+
+pub(crate) fn play_animated_cursors(
+    mut commands: Commands,
+    time: Res<Time>,
+    virtual_time: Res<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+    animated_cursors: Res<Assets<AnimatedCursor>>,
+    mut asset_events: MessageReader<AssetEvent<AnimatedCursor>>,
+    mut query: Query<(
+        Entity,
+        &mut AnimatedCursorPlayer,
+        &mut AnimatedCursorPlayback,
+        Option<&mut CursorIcon>,
+        Option<&DpiAwareCursor>,
+    )>,
+) {
+    let mut events = Vec::new();
+
+    // The frames of modified cursors are resolved again.
+    let modified = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, mut player, mut playback, cursor_icon, dpi_aware_cursor) in &mut query {
+        let Some(c) = animated_cursors.get(&player.cursor) else {
+            continue;
+        };
+
+        let animation = c.animation_or_default(player.animation());
+
+        if !player.is_playing_selected() || modified.contains(&player.cursor.id()) {
+            player.start(c, &mut events);
+        } else if let Some(seek) = playback.seek {
+            playback.seek = None;
+
+            match seek {
+                PlaybackSeek::Step(step) => player.seek(step, &mut events),
+                PlaybackSeek::Restart => player.start(c, &mut events),
+            }
+        } else if !playback.paused {
+            let delta = match playback.clock {
+                PlaybackClock::Default => time.delta(),
+                PlaybackClock::Virtual => virtual_time.delta(),
+                PlaybackClock::Real => real_time.delta(),
+            };
+
+            player.advance(
+                delta.mul_f32(playback.speed.max(0.0)),
+                animation.repeat,
+                &mut events,
+            );
+        }
+
+        for event in events.drain(..) {
+            event.trigger(&mut commands, entity, animation);
+        }
+
+        let Some(frame) = player.frame() else {
+            continue;
+        };
+
+        let atlas_index = dpi_aware_cursor
+            .map_or(0, |dpi_aware_cursor| dpi_aware_cursor.atlas_offset())
+            + frame.atlas_index;
+
+        set_cursor_frame(&mut commands, entity, cursor_icon, c, atlas_index);
+    }
+}
+
+/// Displays the frame at `atlas_index` of `c` as the window's cursor.
+///
+/// The cursor icon is only changed if it doesn't already display the frame, so
+/// that the window's cursor isn't recreated every frame.
+pub(crate) fn set_cursor_frame(
+    commands: &mut Commands,
+    entity: Entity,
+    cursor_icon: Option<Mut<CursorIcon>>,
+    c: &AnimatedCursor,
+    atlas_index: usize,
+) {
+    let hotspot = c.hotspot_or_default(atlas_index);
+
+    let Some(mut cursor_icon) = cursor_icon.filter(|cursor_icon| {
+        matches!(
+            &**cursor_icon,
+            CursorIcon::Custom(CustomCursor::Image(image))
+                if image.handle == c.image && image.texture_atlas.is_some()
+        )
+    }) else {
+        commands.entity(entity).insert(CursorIcon::Custom(
+            CustomCursorImageBuilder::from_animated_cursor(c, Some(atlas_index)).build(),
+        ));
+        return;
+    };
+
+    if let CursorIcon::Custom(CustomCursor::Image(image)) = &*cursor_icon {
+        if image.texture_atlas.as_ref().map(|atlas| atlas.index) == Some(atlas_index)
+            && image.hotspot == hotspot
+        {
+            return;
+        }
+    }
+
+    let CursorIcon::Custom(CustomCursor::Image(ref mut image)) = *cursor_icon else {
+        return;
+    };
+    let Some(atlas) = image.texture_atlas.as_mut() else {
+        return;
+    };
+
+    atlas.index = atlas_index;
+    image.hotspot = hotspot;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bevy_time::{TimePlugin, TimeUpdateStrategy};
+
+    use crate::{
+        ani::{
+            animation::{AnimationClip, AnimationDirection, AnimationDuration, AnimationMarker},
+            asset::AnimatedCursorLoaderSettings,
+        },
+        testing::{ani_bytes, ani_bytes_with_rates, create_app, load_with_settings},
+    };
+
+    use super::*;
+
+    /// Creates an app that plays a cursor of 3 frames of 100 ms each, loaded
+    /// with `settings`.
+    fn setup(
+        settings: impl Fn(&mut AnimatedCursorLoaderSettings) + Send + Sync + 'static,
+    ) -> (App, Entity) {
+        setup_with_bytes(ani_bytes(3, &[32], 6), settings)
+    }
+
+    /// Creates an app that plays the cursor in `bytes`, loaded with `settings`.
+    fn setup_with_bytes(
+        bytes: Vec<u8>,
+        settings: impl Fn(&mut AnimatedCursorLoaderSettings) + Send + Sync + 'static,
+    ) -> (App, Entity) {
+        let (mut app, dir) = create_app();
+        app.add_plugins((TimePlugin, AnimatedCursorPlayerPlugin));
+        dir.insert_asset(Path::new("cursor.ani"), bytes);
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            settings,
+        )
+        .unwrap();
+
+        let entity = app
+            .world_mut()
+            .spawn(AnimatedCursorPlayer::new(handle))
+            .id();
+
+        // The first update starts playing.
+        app.update();
+
+        (app, entity)
+    }
+
+    /// Updates `app` after `millis` milliseconds and returns the atlas index of
+    /// the window's cursor.
+    fn step(app: &mut App, entity: Entity, millis: u64) -> usize {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            millis,
+        )));
+        app.update();
+
+        let Some(CursorIcon::Custom(CustomCursor::Image(image))) =
+            app.world().get::<CursorIcon>(entity)
+        else {
+            panic!("expected a custom cursor image");
+        };
+        image.texture_atlas.as_ref().unwrap().index
+    }
+
+    fn player(app: &App, entity: Entity) -> &AnimatedCursorPlayer {
+        app.world().get::<AnimatedCursorPlayer>(entity).unwrap()
+    }
+
+    fn player_mut(app: &mut App, entity: Entity) -> Mut<'_, AnimatedCursorPlayer> {
+        app.world_mut()
+            .get_mut::<AnimatedCursorPlayer>(entity)
+            .unwrap()
+    }
+
+    /// Edits the loaded cursor that the player on `entity` plays, and updates
+    /// `app` until the player has restarted the modified cursor.
+    fn edit_cursor(app: &mut App, entity: Entity, edit: impl FnOnce(&mut AnimatedCursor)) {
+        let handle = player(app, entity).cursor.clone();
+        let mut cursors = app.world_mut().resource_mut::<Assets<AnimatedCursor>>();
+        edit(&mut cursors.get_mut(&handle).unwrap());
+
+        // The asset event is sent at the end of the first update.
+        step(app, entity, 0);
+        step(app, entity, 0);
+    }
+
+    fn playback(app: &mut App, entity: Entity) -> Mut<'_, AnimatedCursorPlayback> {
+        app.world_mut()
+            .get_mut::<AnimatedCursorPlayback>(entity)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_advance() {
+        let (mut app, entity) = setup(|_| {});
+
+        assert_eq!(step(&mut app, entity, 0), 0);
+        assert_eq!(step(&mut app, entity, 50), 0);
+        assert_eq!(step(&mut app, entity, 60), 1);
+        assert_eq!(step(&mut app, entity, 100), 2);
+        // Looping animations start over.
+        assert_eq!(step(&mut app, entity, 100), 0);
+        assert_eq!(step(&mut app, entity, 200), 2);
+        assert!(!player(&app, entity).is_finished());
+    }
+
+    #[test]
+    fn test_advance_repeat_times() {
+        let (mut app, entity) = setup(|settings| settings.repeat = Some(AnimationRepeat::Times(2)));
+
+        assert_eq!(step(&mut app, entity, 250), 2);
+        assert_eq!(step(&mut app, entity, 100), 0);
+        assert!(!player(&app, entity).is_finished());
+        assert_eq!(step(&mut app, entity, 200), 2);
+        assert!(!player(&app, entity).is_finished());
+        // Finished animations stay on their last frame.
+        assert_eq!(step(&mut app, entity, 100), 2);
+        assert!(player(&app, entity).is_finished());
+        assert_eq!(step(&mut app, entity, 200), 2);
+    }
+
+    #[test]
+    fn test_advance_zero_durations() {
+        let (mut app, entity) = setup(|settings| settings.frame_duration = Some(0));
+
+        // The first frame is held instead of looping forever.
+        assert_eq!(step(&mut app, entity, 100), 0);
+        assert_eq!(step(&mut app, entity, 100), 0);
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let (mut app, entity) = setup(|_| {});
+
+        assert_eq!(step(&mut app, entity, 60), 0);
+        playback(&mut app, entity).pause();
+        assert_eq!(step(&mut app, entity, 100), 0);
+        assert_eq!(step(&mut app, entity, 100), 0);
+        playback(&mut app, entity).toggle();
+        assert!(!playback(&mut app, entity).is_paused());
+        assert_eq!(step(&mut app, entity, 50), 1);
+    }
+
+    #[test]
+    fn test_speed() {
+        let (mut app, entity) = setup(|_| {});
+
+        playback(&mut app, entity).set_speed(2.0);
+        assert_eq!(step(&mut app, entity, 60), 1);
+        assert_eq!(step(&mut app, entity, 100), 0);
+
+        playback(&mut app, entity).set_speed(0.5);
+        assert_eq!(step(&mut app, entity, 100), 0);
+        assert_eq!(step(&mut app, entity, 100), 1);
+
+        // Negative speeds are treated as zero.
+        playback(&mut app, entity).set_speed(-1.0);
+        assert_eq!(step(&mut app, entity, 200), 1);
+    }
+
+    #[test]
+    fn test_seek_and_restart() {
+        let (mut app, entity) = setup(|settings| settings.repeat = Some(AnimationRepeat::Times(1)));
+
+        assert_eq!(step(&mut app, entity, 30), 0);
+
+        // Seeking jumps to the start of the step, without advancing.
+        playback(&mut app, entity).seek(2);
+        assert_eq!(step(&mut app, entity, 50), 2);
+        assert_eq!(player(&app, entity).step(), 2);
+        assert_eq!(step(&mut app, entity, 60), 2);
+        assert!(!player(&app, entity).is_finished());
+        assert_eq!(step(&mut app, entity, 50), 2);
+        assert!(player(&app, entity).is_finished());
+
+        // Seeking past the end jumps to the last step and plays it again.
+        playback(&mut app, entity).seek(10);
+        assert_eq!(step(&mut app, entity, 0), 2);
+        assert!(!player(&app, entity).is_finished());
+
+        playback(&mut app, entity).restart();
+        assert_eq!(step(&mut app, entity, 50), 0);
+        assert_eq!(step(&mut app, entity, 110), 1);
+    }
+
+    #[derive(Default, Resource)]
+    struct TriggeredEvents(Vec<PlayerEvent>);
+
+    /// Records the clip and animation events triggered in `app`.
+    fn record_events(app: &mut App) {
+        app.init_resource::<TriggeredEvents>()
+            .add_observer(
+                |event: On<CursorClipStarted>, mut events: ResMut<TriggeredEvents>| {
+                    events.0.push(PlayerEvent::ClipStarted(event.clip_index));
+                },
+            )
+            .add_observer(
+                |event: On<CursorClipFinished>, mut events: ResMut<TriggeredEvents>| {
+                    events.0.push(PlayerEvent::ClipFinished(event.clip_index));
+                },
+            )
+            .add_observer(
+                |event: On<CursorAnimationLooped>, mut events: ResMut<TriggeredEvents>| {
+                    events.0.push(PlayerEvent::Looped(event.repetition));
+                },
+            )
+            .add_observer(
+                |_: On<CursorAnimationFinished>, mut events: ResMut<TriggeredEvents>| {
+                    events.0.push(PlayerEvent::Finished);
+                },
+            );
+    }
+
+    /// Updates `app` after `millis` milliseconds and returns the events that
+    /// were triggered.
+    fn take_events(app: &mut App, entity: Entity, millis: u64) -> Vec<PlayerEvent> {
+        step(app, entity, millis);
+        std::mem::take(&mut app.world_mut().resource_mut::<TriggeredEvents>().0)
+    }
+
+    #[test]
+    fn test_events() {
+        // Two clips: frames 0 and 1 for 100 ms each, then frame 2 for 200 ms.
+        let bytes = ani_bytes_with_rates(&[32], &[6, 6, 12]);
+
+        let (mut app, dir) = create_app();
+        app.add_plugins((TimePlugin, AnimatedCursorPlayerPlugin));
+        record_events(&mut app);
+        dir.insert_asset(Path::new("cursor.ani"), bytes);
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |settings| settings.repeat = Some(AnimationRepeat::Times(2)),
+        )
+        .unwrap();
+        let entity = app
+            .world_mut()
+            .spawn(AnimatedCursorPlayer::new(handle))
+            .id();
+
+        assert_eq!(
+            take_events(&mut app, entity, 0),
+            vec![PlayerEvent::ClipStarted(0)]
+        );
+        assert_eq!(take_events(&mut app, entity, 150), vec![]);
+        assert_eq!(
+            take_events(&mut app, entity, 100),
+            vec![PlayerEvent::ClipFinished(0), PlayerEvent::ClipStarted(1)]
+        );
+        assert_eq!(
+            take_events(&mut app, entity, 200),
+            vec![
+                PlayerEvent::ClipFinished(1),
+                PlayerEvent::Looped(1),
+                PlayerEvent::ClipStarted(0),
+            ]
+        );
+        assert_eq!(
+            take_events(&mut app, entity, 250),
+            vec![PlayerEvent::ClipFinished(0), PlayerEvent::ClipStarted(1)]
+        );
+        assert_eq!(
+            take_events(&mut app, entity, 100),
+            vec![PlayerEvent::ClipFinished(1), PlayerEvent::Finished]
+        );
+        assert_eq!(take_events(&mut app, entity, 100), vec![]);
+    }
+
+    #[test]
+    fn test_clip_pass_events() {
+        let (mut app, dir) = create_app();
+        app.add_plugins((TimePlugin, AnimatedCursorPlayerPlugin));
+        record_events(&mut app);
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(3, &[32], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |settings| settings.repeat = Some(AnimationRepeat::Times(1)),
+        )
+        .unwrap();
+        {
+            let mut cursors = app.world_mut().resource_mut::<Assets<AnimatedCursor>>();
+            let animation = &mut cursors.get_mut(&handle).unwrap().animation;
+            animation.clips[0].repeat = Some(2);
+            animation.direction = AnimationDirection::PingPong;
+        }
+        // Send the asset event of the edit before the player starts.
+        app.update();
+        let entity = app
+            .world_mut()
+            .spawn(AnimatedCursorPlayer::new(handle))
+            .id();
+
+        // The clip plays twice forwards, `[0, 1, 2]`, and then twice backwards
+        // without the first and last frames, `[1, 0]` and `[2, 1]`.
+        let pass = vec![PlayerEvent::ClipFinished(0), PlayerEvent::ClipStarted(0)];
+        assert_eq!(
+            take_events(&mut app, entity, 0),
+            vec![PlayerEvent::ClipStarted(0)]
+        );
+        assert_eq!(take_events(&mut app, entity, 250), vec![]);
+        assert_eq!(take_events(&mut app, entity, 100), pass);
+        assert_eq!(take_events(&mut app, entity, 300), pass);
+        assert_eq!(take_events(&mut app, entity, 200), pass);
+        assert_eq!(
+            take_events(&mut app, entity, 200),
+            vec![PlayerEvent::ClipFinished(0), PlayerEvent::Finished]
+        );
+    }
+
+    #[test]
+    fn test_modified_cursor() {
+        let (mut app, entity) = setup(|_| {});
+        assert_eq!(step(&mut app, entity, 150), 1);
+
+        // The frames of a modified cursor are resolved again, and it plays from
+        // the start.
+        edit_cursor(&mut app, entity, |c| {
+            c.animation.clips[0].atlas_indices = vec![2, 0];
+        });
+        assert_eq!(player(&app, entity).step_count(), 2);
+        assert_eq!(step(&mut app, entity, 0), 2);
+        assert_eq!(step(&mut app, entity, 100), 0);
+    }
+
+    #[derive(Default, Resource)]
+    struct HitMarkers(Vec<(usize, String)>);
+
+    #[test]
+    fn test_markers() {
+        let (mut app, entity) = setup(|_| {});
+        app.init_resource::<HitMarkers>().add_observer(
+            |event: On<CursorMarkerHit>, mut markers: ResMut<HitMarkers>| {
+                markers.0.push((event.clip_index, event.name.clone()));
+            },
+        );
+        edit_cursor(&mut app, entity, |c| {
+            c.animation.clips[0].markers = vec![
+                AnimationMarker {
+                    name: "flip".to_string(),
+                    frame: 1,
+                },
+                AnimationMarker {
+                    name: "end".to_string(),
+                    frame: 2,
+                },
+            ];
+        });
+
+        let mut take_markers = |millis| {
+            step(&mut app, entity, millis);
+            std::mem::take(&mut app.world_mut().resource_mut::<HitMarkers>().0)
+                .into_iter()
+                .map(|(clip_index, name)| {
+                    assert_eq!(clip_index, 0);
+                    name
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(take_markers(50), Vec::<String>::new());
+        assert_eq!(take_markers(50), vec!["flip"]);
+        assert_eq!(take_markers(100), vec!["end"]);
+        assert_eq!(take_markers(100), Vec::<String>::new());
+        // Markers of frames that are passed over in a single update are hit too.
+        assert_eq!(take_markers(250), vec!["flip", "end"]);
+    }
+
+    #[test]
+    fn test_named_animations() {
+        let (mut app, entity) = setup(|_| {});
+        edit_cursor(&mut app, entity, |c| {
+            c.animations.insert(
+                "busy".to_string(),
+                Animation {
+                    clips: vec![AnimationClip {
+                        atlas_indices: vec![2, 1],
+                        duration: AnimationDuration::PerFrame(50),
+                        direction: AnimationDirection::Forwards,
+                        repeat: None,
+                        markers: Vec::new(),
+                    }],
+                    repeat: AnimationRepeat::Loop,
+                    direction: AnimationDirection::Forwards,
+                },
+            );
+        });
+
+        assert_eq!(step(&mut app, entity, 150), 1);
+
+        player_mut(&mut app, entity).play("busy");
+        assert_eq!(step(&mut app, entity, 0), 2);
+        assert_eq!(player(&app, entity).step_count(), 2);
+        assert_eq!(step(&mut app, entity, 50), 1);
+
+        // Switching to the animation that is playing doesn't restart it.
+        player_mut(&mut app, entity).play("busy");
+        assert_eq!(step(&mut app, entity, 0), 1);
+
+        player_mut(&mut app, entity).play_default();
+        assert_eq!(player(&app, entity).animation(), None);
+        assert_eq!(step(&mut app, entity, 0), 0);
+        assert_eq!(player(&app, entity).step_count(), 3);
+
+        // Unknown animations play the default animation.
+        player_mut(&mut app, entity).play("missing");
+        assert_eq!(step(&mut app, entity, 50), 0);
+        assert_eq!(step(&mut app, entity, 150), 1);
+
+        let handle = player(&app, entity).cursor.clone();
+        let other = app
+            .world_mut()
+            .spawn(AnimatedCursorPlayer::new(handle).with_animation("busy"))
+            .id();
+        assert_eq!(step(&mut app, other, 0), 2);
+    }
+}
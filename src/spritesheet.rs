@@ -0,0 +1,178 @@
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::TextureAtlas;
+use bevy_reflect::prelude::*;
+use bevy_sprite::Sprite;
+use bevy_spritesheet_animation::plugin::AnimationSystemSet;
+use bevy_window::CursorIcon;
+
+use crate::{ani::asset::AnimatedCursor, dpi::DpiAwareCursor, player::set_cursor_frame};
+
+/// A plugin that mirrors sprites animated by `bevy_spritesheet_animation` onto
+/// the custom cursors of windows.
+///
+/// Add `bevy_spritesheet_animation`'s own plugin as well to play the
+/// animations.
+pub struct SpritesheetCursorPlugin;
+
+impl Plugin for SpritesheetCursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SpritesheetCursor>().add_systems(
+            PostUpdate,
+            apply_spritesheet_cursor_frames.after(AnimationSystemSet),
+        );
+    }
+}
+
+/// Displays the current frame of a sprite animated by
+/// `bevy_spritesheet_animation` as the custom cursor of a window.
+///
+/// Insert this on an entity with a [`Sprite`] created with [`cursor_sprite`]
+/// and a `SpritesheetAnimation`, e.g. one whose animation was converted with
+/// [`Animation::to_spritesheet_animation`]. Whenever the sprite's atlas index
+/// changes, the window's [`CursorIcon`] is switched to the same frame along
+/// with its hotspot. The sprite itself is usually hidden.
+///
+/// If the window also has a [`DpiAwareCursor`], frames are taken from the
+/// variant that it selected.
+///
+/// [`Animation::to_spritesheet_animation`]: crate::ani::animation::Animation::to_spritesheet_animation
+#[derive(Clone, Component, Debug, Reflect)]
+#[reflect(Component, Debug)]
+pub struct SpritesheetCursor {
+    /// The cursor that the sprite's frames are taken from.
+    pub cursor: Handle<AnimatedCursor>,
+    /// The window entity whose cursor is updated.
+    pub window: Entity,
+}
+
+impl SpritesheetCursor {
+    /// Creates a new [`SpritesheetCursor`] that applies frames of `cursor` to
+    /// `window`.
+    pub fn new(cursor: Handle<AnimatedCursor>, window: Entity) -> Self {
+        Self { cursor, window }
+    }
+}
+
+/// Returns a sprite that displays the first frame of `c`, for animating with
+/// `bevy_spritesheet_animation`.
+pub fn cursor_sprite(c: &AnimatedCursor) -> Sprite {
+    Sprite::from_atlas_image(
+        c.image.clone(),
+        TextureAtlas {
+            layout: c.texture_atlas_layout.clone(),
+            index: 0,
+        },
+    )
+}
+
+fn apply_spritesheet_cursor_frames(
+    mut commands: Commands,
+    animated_cursors: Res<Assets<AnimatedCursor>>,
+    query: Query<(&SpritesheetCursor, &Sprite), Changed<Sprite>>,
+    mut windows: Query<(Option<&mut CursorIcon>, Option<&DpiAwareCursor>)>,
+) {
+    for (spritesheet_cursor, sprite) in &query {
+        let Some(c) = animated_cursors.get(&spritesheet_cursor.cursor) else {
+            continue;
+        };
+        let Some(atlas) = sprite.texture_atlas.as_ref() else {
+            continue;
+        };
+        let Ok((cursor_icon, dpi_aware_cursor)) = windows.get_mut(spritesheet_cursor.window) else {
+            continue;
+        };
+
+        let atlas_index = dpi_aware_cursor
+            .map_or(0, |dpi_aware_cursor| dpi_aware_cursor.atlas_offset())
+            + atlas.index;
+
+        set_cursor_frame(
+            &mut commands,
+            spritesheet_cursor.window,
+            cursor_icon,
+            c,
+            atlas_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use bevy_spritesheet_animation::prelude::{
+        Spritesheet, SpritesheetAnimation, SpritesheetAnimationPlugin,
+    };
+    use bevy_time::{TimePlugin, TimeUpdateStrategy};
+    use bevy_window::CustomCursor;
+
+    use crate::{
+        ani::asset::AnimatedCursorLoaderSettings,
+        testing::{ani_bytes, create_app, load_with_settings},
+    };
+
+    use super::*;
+
+    /// Updates `app` after `millis` milliseconds and returns the atlas index and
+    /// hotspot of the cursor of `window`.
+    fn step(app: &mut App, window: Entity, millis: u64) -> (usize, (u16, u16)) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            millis,
+        )));
+        app.update();
+
+        let Some(CursorIcon::Custom(CustomCursor::Image(image))) =
+            app.world().get::<CursorIcon>(window)
+        else {
+            panic!("expected a custom cursor image");
+        };
+        (image.texture_atlas.as_ref().unwrap().index, image.hotspot)
+    }
+
+    #[test]
+    fn test_apply_frames() {
+        let (mut app, dir) = create_app();
+        app.add_plugins((
+            TimePlugin,
+            SpritesheetAnimationPlugin,
+            SpritesheetCursorPlugin,
+        ));
+        dir.insert_asset(Path::new("cursor.ani"), ani_bytes(3, &[32], 6));
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "cursor.ani",
+            |_| {},
+        )
+        .unwrap();
+
+        let c = {
+            let mut cursors = app.world_mut().resource_mut::<Assets<AnimatedCursor>>();
+            let mut c = cursors.get_mut(&handle).unwrap();
+            c.hotspots.overrides.insert(1, (3, 4));
+            c.clone()
+        };
+
+        let spritesheet = Spritesheet::new(&c.image, 3, 1);
+        let animation = c.animation.to_spritesheet_animation(&spritesheet).unwrap();
+        let animation = app
+            .world_mut()
+            .resource_mut::<Assets<bevy_spritesheet_animation::prelude::Animation>>()
+            .add(animation);
+
+        let window = app.world_mut().spawn_empty().id();
+        app.world_mut().spawn((
+            cursor_sprite(&c),
+            SpritesheetAnimation::new(animation),
+            SpritesheetCursor::new(handle, window),
+        ));
+
+        assert_eq!(step(&mut app, window, 0), (0, (16, 16)));
+        assert_eq!(step(&mut app, window, 150), (1, (3, 4)));
+        assert_eq!(step(&mut app, window, 100), (2, (16, 16)));
+        // The animation loops.
+        assert_eq!(step(&mut app, window, 100), (0, (16, 16)));
+    }
+}
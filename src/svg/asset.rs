@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+
+use bevy_app::prelude::*;
+use bevy_asset::{
+    io::Reader, prelude::*, AssetLoader, LoadContext, ParseAssetPathError, ReadAssetBytesError,
+    RenderAssetUsages,
+};
+use bevy_image::{Image, TextureAtlasBuilderError};
+use bevy_math::UVec2;
+use bevy_reflect::prelude::*;
+use image::DynamicImage;
+use resvg::usvg;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    ani::{
+        animation::{Animation, AnimationRepeat},
+        asset::AnimatedCursor,
+    },
+    atlas::build_cursor_atlas,
+    cur::asset::StaticCursor,
+    variant::{CursorVariant, DEFAULT_CURSOR_SIZE},
+};
+
+use super::{rasterize, ParseManifestError, SvgCursorManifest};
+
+/// The extensions of manifests that are loaded as animated cursors.
+const EXTENSIONS: &[&str] = &["hl"];
+
+/// The extensions of manifests that are loaded as static cursors.
+///
+/// These are distinct from [`EXTENSIONS`] so that every extension has a
+/// single loader, like the `cur.xcur` extension of Xcursor files.
+const STATIC_EXTENSIONS: &[&str] = &["cur.hl"];
+
+pub struct SvgCursorAssetPlugin;
+
+impl Plugin for SvgCursorAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset_loader::<SvgStaticCursorLoader>()
+            .init_asset_loader::<SvgAnimatedCursorLoader>();
+    }
+}
+
+/// A loader for static cursor assets from hyprcursor-style `meta.hl` files.
+///
+/// The first frame is rasterized at each of the settings' sizes, which become
+/// the cursor's entries and variants.
+///
+/// Manifests are loaded by this loader with the `.cur.hl` extension, e.g.
+/// `meta.cur.hl`, since the plain extension belongs to
+/// [`SvgAnimatedCursorLoader`].
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct SvgStaticCursorLoader;
+
+/// A loader for animated cursor assets from hyprcursor-style `meta.hl` files.
+///
+/// Every frame is rasterized at each of the settings' sizes, which become the
+/// cursor's variants, and the animation uses each frame's delay.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct SvgAnimatedCursorLoader;
+
+/// Settings for loading SVG cursors with [`SvgStaticCursorLoader`] and
+/// [`SvgAnimatedCursorLoader`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SvgCursorLoaderSettings {
+    /// The sizes, in pixels, to rasterize the cursor at. Each size becomes a
+    /// variant, so that [`DpiAwareCursor`](crate::dpi::DpiAwareCursor) can pick
+    /// the one that suits the window's scale factor.
+    ///
+    /// Defaults to [`DEFAULT_CURSOR_SIZE`] and twice that.
+    pub sizes: Vec<u32>,
+    /// The usage of the texture atlas image.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+    /// If set, overrides the duration of every frame of animated cursors, in
+    /// milliseconds.
+    pub frame_duration: Option<u32>,
+    /// If set, overrides the repeat behavior of animated cursors, which loop
+    /// by default.
+    pub repeat: Option<AnimationRepeat>,
+}
+
+impl Default for SvgCursorLoaderSettings {
+    fn default() -> Self {
+        Self {
+            sizes: vec![DEFAULT_CURSOR_SIZE, DEFAULT_CURSOR_SIZE * 2],
+            asset_usage: RenderAssetUsages::default(),
+            frame_duration: None,
+            repeat: None,
+        }
+    }
+}
+
+/// Possible errors that can be produced by [`SvgStaticCursorLoader`] and
+/// [`SvgAnimatedCursorLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SvgCursorLoaderError {
+    /// An [IO](std::io) error.
+    #[error("could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [ParseManifestError] error.
+    #[error("could not parse cursor manifest: {0}")]
+    ParseManifestError(#[from] ParseManifestError),
+    /// A [ReadAssetBytesError] error.
+    #[error("could not read SVG file: {0}")]
+    ReadAssetBytesError(#[from] ReadAssetBytesError),
+    /// A [ParseAssetPathError] error.
+    #[error("invalid SVG file path: {0}")]
+    ParseAssetPathError(#[from] ParseAssetPathError),
+    /// A [usvg::Error] error.
+    #[error("could not parse SVG: {0}")]
+    SvgError(#[from] usvg::Error),
+    #[error("cursor manifest has no frames")]
+    MissingFrames,
+    #[error("invalid cursor size: {0}")]
+    InvalidSize(u32),
+    #[error("could not build texture atlas: {0}")]
+    TextureAtlasBuilderError(#[from] TextureAtlasBuilderError),
+}
+
+/// A cursor rasterized at every size, with the frames of each size in order.
+struct RasterizedCursor {
+    manifest: SvgCursorManifest,
+    frames: Vec<(Image, (u16, u16))>,
+    variants: Vec<CursorVariant>,
+}
+
+/// Parses the manifest and rasterizes its first `frame_count` frames, or all
+/// of them if `None`, at each of the settings' sizes.
+async fn rasterize_cursor(
+    reader: &mut dyn Reader,
+    settings: &SvgCursorLoaderSettings,
+    load_context: &mut LoadContext<'_>,
+    frame_count: Option<usize>,
+) -> Result<RasterizedCursor, SvgCursorLoaderError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+
+    let manifest = SvgCursorManifest::parse(&String::from_utf8_lossy(&bytes))?;
+    if manifest.frames.is_empty() {
+        return Err(SvgCursorLoaderError::MissingFrames);
+    }
+    let frame_count = frame_count.map_or(manifest.frames.len(), |count| {
+        count.min(manifest.frames.len())
+    });
+
+    let mut trees = Vec::new();
+    for frame in &manifest.frames[..frame_count] {
+        let path = load_context.path().resolve_embed_str(&frame.path)?;
+        let bytes = load_context.read_asset_bytes(path).await?;
+        trees.push(usvg::Tree::from_data(&bytes, &usvg::Options::default())?);
+    }
+
+    let mut frames = Vec::new();
+    let mut variants = Vec::new();
+    for &size in &settings.sizes {
+        variants.push(CursorVariant {
+            size: UVec2::splat(size),
+            atlas_offset: frames.len(),
+        });
+
+        for tree in &trees {
+            let image = rasterize(tree, size, manifest.nominal_size)
+                .ok_or(SvgCursorLoaderError::InvalidSize(size))?;
+            frames.push((
+                Image::from_dynamic(
+                    DynamicImage::ImageRgba8(image),
+                    true,
+                    RenderAssetUsages::MAIN_WORLD,
+                ),
+                manifest.hotspot(tree, size),
+            ));
+        }
+    }
+
+    if frames.is_empty() {
+        return Err(SvgCursorLoaderError::InvalidSize(0));
+    }
+
+    Ok(RasterizedCursor {
+        manifest,
+        frames,
+        variants,
+    })
+}
+
+impl AssetLoader for SvgStaticCursorLoader {
+    type Asset = StaticCursor;
+    type Settings = SvgCursorLoaderSettings;
+    type Error = SvgCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let cursor = rasterize_cursor(reader, settings, load_context, Some(1)).await?;
+
+        let atlas =
+            build_cursor_atlas(load_context, "", cursor.frames, false, settings.asset_usage)?;
+
+        Ok(StaticCursor {
+            image: atlas.image,
+            texture_atlas_layout: atlas.texture_atlas_layout,
+            hotspots: atlas.hotspots,
+            variants: cursor.variants,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        STATIC_EXTENSIONS
+    }
+}
+
+impl AssetLoader for SvgAnimatedCursorLoader {
+    type Asset = AnimatedCursor;
+    type Settings = SvgCursorLoaderSettings;
+    type Error = SvgCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let cursor = rasterize_cursor(reader, settings, load_context, None).await?;
+
+        let atlas =
+            build_cursor_atlas(load_context, "", cursor.frames, false, settings.asset_usage)?;
+
+        let mut animation = Animation::from_frame_durations(
+            cursor
+                .manifest
+                .frames
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| (i, settings.frame_duration.unwrap_or(frame.delay))),
+        );
+        if let Some(repeat) = settings.repeat {
+            animation.repeat = repeat;
+        }
+
+        Ok(AnimatedCursor {
+            metadata: None,
+            info: None,
+            image: atlas.image,
+            texture_atlas_layout: atlas.texture_atlas_layout,
+            hotspots: atlas.hotspots,
+            variants: cursor.variants,
+            animation,
+            animations: BTreeMap::new(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        EXTENSIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        svg::DEFAULT_FRAME_DELAY,
+        testing::{create_app, load_with_settings},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_load_by_extension() {
+        let (mut app, dir) = create_app();
+        let manifest = "hotspot_x = 0.5\n\
+                        hotspot_y = 0.5\n\
+                        define_size = 0, wait_0.svg\n\
+                        define_size = 0, wait_1.svg, 50\n";
+        dir.insert_asset_text(Path::new("wait/meta.hl"), manifest);
+        dir.insert_asset_text(Path::new("wait/meta.cur.hl"), manifest);
+        for name in ["wait_0.svg", "wait_1.svg"] {
+            dir.insert_asset_text(
+                &Path::new("wait").join(name),
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16"/>"#,
+            );
+        }
+
+        let handle = load_with_settings::<AnimatedCursor, SvgCursorLoaderSettings>(
+            &mut app,
+            "wait/meta.hl",
+            |s| s.sizes = vec![16, 32],
+        )
+        .unwrap();
+        let animated_cursors = app.world().resource::<Assets<AnimatedCursor>>();
+        let cursor = animated_cursors.get(&handle).unwrap();
+        assert_eq!(
+            cursor.animation,
+            Animation::from_frame_durations([(0, DEFAULT_FRAME_DELAY), (1, 50)])
+        );
+        assert_eq!(cursor.variants.len(), 2);
+        assert_eq!(cursor.variants[1].atlas_offset, 2);
+        assert_eq!(cursor.hotspot_or_default(2), (16, 16));
+
+        let handle = load_with_settings::<StaticCursor, SvgCursorLoaderSettings>(
+            &mut app,
+            "wait/meta.cur.hl",
+            |s| s.sizes = vec![16, 32],
+        )
+        .unwrap();
+        let static_cursors = app.world().resource::<Assets<StaticCursor>>();
+        let cursor = static_cursors.get(&handle).unwrap();
+        assert_eq!(cursor.variants.len(), 2);
+        assert_eq!(cursor.hotspots.get_or_default(1), (16, 16));
+    }
+}
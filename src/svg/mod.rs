@@ -0,0 +1,246 @@
+pub mod asset;
+
+use std::fmt;
+
+use image::RgbaImage;
+use resvg::{tiny_skia, usvg};
+
+/// The delay of frames that don't set one, in milliseconds.
+pub const DEFAULT_FRAME_DELAY: u32 = 100;
+
+/// The description of a cursor in a hyprcursor-style `meta.hl` file.
+///
+/// ```text
+/// hotspot_x = 0.25
+/// hotspot_y = 0.1
+/// nominal_size = 1.0
+/// define_override = hand2
+/// define_size = 0, frame_0.svg, 100
+/// define_size = 0, frame_1.svg, 100
+/// ```
+///
+/// Each `define_size` line is a frame, with the SVG file to rasterize and an
+/// optional delay in milliseconds, which defaults to [`DEFAULT_FRAME_DELAY`].
+/// The size is ignored, since SVG frames can be rasterized at any size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgCursorManifest {
+    /// The x coordinate of the hotspot, from `0.0` at the left edge to `1.0`
+    /// at the right edge of the rasterized image.
+    pub hotspot_x: f32,
+    /// The y coordinate of the hotspot, from `0.0` at the top edge to `1.0` at
+    /// the bottom edge of the rasterized image.
+    pub hotspot_y: f32,
+    /// The size of the image relative to the size of the cursor. Images are
+    /// drawn from the top left corner.
+    pub nominal_size: f32,
+    /// Other names of the cursor.
+    pub overrides: Vec<String>,
+    /// The frames, in order.
+    pub frames: Vec<SvgCursorFrame>,
+}
+
+impl Default for SvgCursorManifest {
+    fn default() -> Self {
+        Self {
+            hotspot_x: 0.0,
+            hotspot_y: 0.0,
+            nominal_size: 1.0,
+            overrides: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+}
+
+/// A frame of an SVG cursor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SvgCursorFrame {
+    /// The path of the SVG file, relative to the manifest.
+    pub path: String,
+    /// How long the frame is displayed for, in milliseconds, if the cursor is
+    /// animated.
+    pub delay: u32,
+}
+
+#[derive(Debug)]
+pub enum ParseManifestError {
+    InvalidValue(String, String),
+}
+
+impl std::error::Error for ParseManifestError {}
+
+impl fmt::Display for ParseManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseManifestError::InvalidValue(key, value) => {
+                write!(f, "invalid value for {}: {:?}", key, value)
+            }
+        }
+    }
+}
+
+impl SvgCursorManifest {
+    /// Parses a `meta.hl` file. Unknown keys, such as `resize_algorithm`, and
+    /// comments are ignored.
+    pub fn parse(s: &str) -> Result<Self, ParseManifestError> {
+        let mut manifest = SvgCursorManifest::default();
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            let invalid = || ParseManifestError::InvalidValue(key.to_string(), value.to_string());
+
+            match key {
+                "hotspot_x" => manifest.hotspot_x = value.parse().map_err(|_| invalid())?,
+                "hotspot_y" => manifest.hotspot_y = value.parse().map_err(|_| invalid())?,
+                "nominal_size" => manifest.nominal_size = value.parse().map_err(|_| invalid())?,
+                "define_override" => manifest.overrides.push(value.to_string()),
+                "define_size" => {
+                    let mut fields = value.split(',').map(str::trim);
+                    let (Some(_size), Some(path)) = (fields.next(), fields.next()) else {
+                        return Err(invalid());
+                    };
+                    let delay = match fields.next() {
+                        Some(delay) => delay.parse().map_err(|_| invalid())?,
+                        None => DEFAULT_FRAME_DELAY,
+                    };
+                    manifest.frames.push(SvgCursorFrame {
+                        path: path.to_string(),
+                        delay,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Returns the hotspot of the cursor at `size` pixels, relative to `tree`
+    /// as [`rasterize`] draws it with this manifest's nominal size.
+    pub fn hotspot(&self, tree: &usvg::Tree, size: u32) -> (u16, u16) {
+        let tree_size = tree.size();
+        let scale = size as f32 * self.nominal_size / tree_size.width().max(tree_size.height());
+        let max = size.saturating_sub(1) as f32;
+        (
+            (self.hotspot_x * tree_size.width() * scale).clamp(0.0, max) as u16,
+            (self.hotspot_y * tree_size.height() * scale).clamp(0.0, max) as u16,
+        )
+    }
+}
+
+/// Rasterizes an SVG image onto a transparent `size` by `size` image, scaled
+/// to fit `size * nominal_size` pixels while keeping its aspect ratio.
+///
+/// Returns `None` if `size` is zero.
+pub fn rasterize(tree: &usvg::Tree, size: u32, nominal_size: f32) -> Option<RgbaImage> {
+    let mut pixmap = tiny_skia::Pixmap::new(size, size)?;
+
+    let tree_size = tree.size();
+    let scale = size as f32 * nominal_size / tree_size.width().max(tree_size.height());
+    resvg::render(
+        tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // Pixmaps have premultiplied alpha, while images have straight alpha.
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    RgbaImage::from_raw(size, size, pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = SvgCursorManifest::parse(
+            "# An animated cursor\n\
+             resize_algorithm = bilinear\n\
+             hotspot_x = 0.5\n\
+             hotspot_y = 0.25\n\
+             define_override = watch\n\
+             define_size = 0, wait_0.svg, 50\n\
+             define_size = 0, wait_1.svg\n",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.nominal_size, 1.0);
+        assert_eq!(manifest.overrides, vec!["watch"]);
+        assert_eq!(
+            manifest.frames,
+            vec![
+                SvgCursorFrame {
+                    path: "wait_0.svg".to_string(),
+                    delay: 50,
+                },
+                SvgCursorFrame {
+                    path: "wait_1.svg".to_string(),
+                    delay: DEFAULT_FRAME_DELAY,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hotspot() {
+        let mut manifest = SvgCursorManifest {
+            hotspot_x: 0.5,
+            hotspot_y: 0.25,
+            ..Default::default()
+        };
+        let square = usvg::Tree::from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16"/>"#,
+            &usvg::Options::default(),
+        )
+        .unwrap();
+        let wide = usvg::Tree::from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="8"/>"#,
+            &usvg::Options::default(),
+        )
+        .unwrap();
+
+        assert_eq!(manifest.hotspot(&square, 32), (16, 8));
+        assert_eq!(manifest.hotspot(&square, 64), (32, 16));
+        assert_eq!(manifest.hotspot(&wide, 64), (32, 8));
+
+        manifest.nominal_size = 0.5;
+        assert_eq!(manifest.hotspot(&square, 64), (16, 8));
+        assert_eq!(manifest.hotspot(&wide, 64), (16, 4));
+
+        manifest.hotspot_x = 1.0;
+        manifest.hotspot_y = 1.0;
+        assert_eq!(manifest.hotspot(&square, 64), (32, 32));
+        manifest.nominal_size = 1.0;
+        assert_eq!(manifest.hotspot(&square, 64), (63, 63));
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16">
+            <rect x="0" y="0" width="8" height="16" fill="#ff0000"/>
+        </svg>"##;
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+
+        let image = rasterize(&tree, 64, 1.0).unwrap();
+
+        assert_eq!(image.dimensions(), (64, 64));
+        assert_eq!(image.get_pixel(8, 32).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(56, 32).0, [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Helpers for tests that load cursor assets.
+
+#[cfg(feature = "packed_asset")]
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use bevy_app::{App, TaskPoolPlugin};
+use bevy_asset::{
+    io::{
+        memory::{Dir, MemoryAssetReader},
+        AssetSourceBuilder, AssetSourceId,
+    },
+    meta::Settings,
+    prelude::*,
+    AssetPlugin, LoadState,
+};
+#[cfg(feature = "packed_asset")]
+use bevy_asset::{
+    saver::{AssetSaver, SavedAsset, SavedAssetBuilder},
+    AssetPath,
+};
+use bevy_image::{Image, TextureAtlasLayout};
+use ico::{IconDir, IconDirEntry, IconImage, ResourceType};
+
+use crate::CursorAssetPlugin;
+
+/// Creates an app with the cursor asset loaders that loads assets from the
+/// returned in-memory directory.
+pub(crate) fn create_app() -> (App, Dir) {
+    let mut app = App::new();
+    let dir = Dir::default();
+    let root = dir.clone();
+    app.register_asset_source(
+        AssetSourceId::Default,
+        AssetSourceBuilder::new(move || Box::new(MemoryAssetReader { root: root.clone() })),
+    )
+    .add_plugins((
+        TaskPoolPlugin::default(),
+        AssetPlugin {
+            watch_for_changes_override: Some(false),
+            use_asset_processor_override: Some(false),
+            ..Default::default()
+        },
+        CursorAssetPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_asset::<TextureAtlasLayout>();
+    (app, dir)
+}
+
+/// Loads the asset at `path` with `settings`, updating `app` until it has
+/// loaded. Returns the error message if loading fails.
+pub(crate) fn load_with_settings<A: Asset, S: Settings>(
+    app: &mut App,
+    path: &str,
+    settings: impl Fn(&mut S) + Send + Sync + 'static,
+) -> Result<Handle<A>, String> {
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load_builder()
+        .with_settings(settings)
+        .load(path.to_string());
+
+    for _ in 0..1000 {
+        app.update();
+        match app
+            .world()
+            .resource::<AssetServer>()
+            .load_state(handle.id())
+        {
+            LoadState::Loaded => return Ok(handle),
+            LoadState::Failed(error) => return Err(error.to_string()),
+            _ => {}
+        }
+    }
+
+    panic!("{} did not load", path);
+}
+
+/// Saves `asset` with `saver`, along with the `image` and
+/// `texture_atlas_layout` labeled assets that it refers to in `app`, and
+/// returns the saved bytes.
+#[cfg(feature = "packed_asset")]
+pub(crate) fn save_with_labeled_atlas<S: AssetSaver<Settings = ()>>(
+    app: &App,
+    saver: &S,
+    asset: &S::Asset,
+    image: &Handle<Image>,
+    texture_atlas_layout: &Handle<TextureAtlasLayout>,
+) -> Result<Vec<u8>, S::Error> {
+    let world = app.world();
+    let image_value = world.resource::<Assets<Image>>().get(image).unwrap();
+    let layout_value = world
+        .resource::<Assets<TextureAtlasLayout>>()
+        .get(texture_atlas_layout)
+        .unwrap();
+
+    let mut builder = SavedAssetBuilder::new(
+        world.resource::<AssetServer>().clone(),
+        AssetPath::from("cursor"),
+    );
+    builder.add_labeled_asset_with_existing_handle(
+        "image",
+        SavedAsset::from_asset(image_value),
+        image.clone(),
+    );
+    builder.add_labeled_asset_with_existing_handle(
+        "texture_atlas_layout",
+        SavedAsset::from_asset(layout_value),
+        texture_atlas_layout.clone(),
+    );
+
+    let mut bytes = Vec::new();
+    let save = saver.save(
+        &mut bytes,
+        builder.build(asset),
+        &(),
+        AssetPath::from("cursor"),
+    );
+    // Writing to memory never waits, so the save completes when first polled.
+    let Poll::Ready(result) = pin!(save).poll(&mut Context::from_waker(Waker::noop())) else {
+        panic!("saving did not complete");
+    };
+    result.map(|_| bytes)
+}
+
+/// Encodes a cursor with a square, opaque entry of each size, whose hotspot
+/// is its center.
+pub(crate) fn cursor_icon_dir(sizes: &[u32]) -> IconDir {
+    let mut icon_dir = IconDir::new(ResourceType::Cursor);
+    for &size in sizes {
+        let mut image =
+            IconImage::from_rgba_data(size, size, vec![255; (size * size * 4) as usize]);
+        image.set_cursor_hotspot(Some((size as u16 / 2, size as u16 / 2)));
+        icon_dir.add_entry(IconDirEntry::encode(&image).unwrap());
+    }
+    icon_dir
+}
+
+/// Encodes a .CUR file, see [`cursor_icon_dir`].
+pub(crate) fn cur_bytes(sizes: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    cursor_icon_dir(sizes).write(&mut bytes).unwrap();
+    bytes
+}
+
+/// Encodes a RIFF chunk, padded to an even length.
+pub(crate) fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+    if !data.len().is_multiple_of(2) {
+        bytes.push(0);
+    }
+    bytes
+}
+
+/// Encodes an .ANI file with `frame_count` frames of cursor entries of each
+/// size, see [`cursor_icon_dir`], that are each displayed for
+/// `ticks_per_frame` ticks.
+pub(crate) fn ani_bytes(frame_count: u32, sizes: &[u32], ticks_per_frame: u32) -> Vec<u8> {
+    encode_ani(&vec![sizes; frame_count as usize], ticks_per_frame, &[])
+}
+
+/// Encodes an .ANI file like [`ani_bytes`], with a frame for each of `rates`
+/// that is displayed for that many ticks.
+pub(crate) fn ani_bytes_with_rates(sizes: &[u32], rates: &[u32]) -> Vec<u8> {
+    let rate = rates
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect::<Vec<_>>();
+    encode_ani(&vec![sizes; rates.len()], rates[0], &chunk(b"rate", &rate))
+}
+
+/// Builds an Xcursor file with an image chunk for each of `images`, given as
+/// the nominal size, size, hotspot, delay and ARGB color of the image.
+pub(crate) fn xcursor_bytes(images: &[(u32, u32, (u32, u32), u32, u32)]) -> Vec<u8> {
+    let header_size = 16;
+    let toc_size = 12 * images.len() as u32;
+
+    let mut bytes = b"Xcur".to_vec();
+    for value in [header_size, 0x1_0000, images.len() as u32] {
+        bytes.extend(value.to_le_bytes());
+    }
+
+    let mut chunks = Vec::new();
+    for &(nominal_size, size, (xhot, yhot), delay, argb) in images {
+        let position = header_size + toc_size + chunks.len() as u32;
+        for value in [0xfffd_0002, nominal_size, position] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        for value in [
+            36,
+            0xfffd_0002,
+            nominal_size,
+            1,
+            size,
+            size,
+            xhot,
+            yhot,
+            delay,
+        ] {
+            chunks.extend(u32::to_le_bytes(value));
+        }
+        for _ in 0..size * size {
+            chunks.extend(u32::to_le_bytes(argb));
+        }
+    }
+
+    bytes.extend(chunks);
+    bytes
+}
+
+/// Encodes an .ANI file like [`ani_bytes`], with a frame of cursor entries of
+/// the given sizes for each of `frame_sizes`.
+pub(crate) fn ani_bytes_with_frame_sizes(frame_sizes: &[&[u32]], ticks_per_frame: u32) -> Vec<u8> {
+    encode_ani(frame_sizes, ticks_per_frame, &[])
+}
+
+fn encode_ani(frame_sizes: &[&[u32]], ticks_per_frame: u32, chunks: &[u8]) -> Vec<u8> {
+    let frame_count = frame_sizes.len() as u32;
+    let header = [36, frame_count, frame_count, 0, 0, 0, 0, ticks_per_frame, 1]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect::<Vec<_>>();
+
+    let mut frames = b"fram".to_vec();
+    for sizes in frame_sizes {
+        frames.extend(chunk(b"icon", &cur_bytes(sizes)));
+    }
+
+    let mut contents = b"ACON".to_vec();
+    contents.extend(chunk(b"anih", &header));
+    contents.extend(chunk(b"LIST", &frames));
+    contents.extend_from_slice(chunks);
+
+    chunk(b"RIFF", &contents)
+}
@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy_app::prelude::*;
+use bevy_asset::{
+    io::{AssetReaderError, Reader},
+    prelude::*,
+    AssetLoader, AssetPath, LoadContext, ParseAssetPathError, ReadAssetBytesError,
+    RenderAssetUsages,
+};
+use bevy_reflect::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    ani::asset::{AnimatedCursor, AnimatedCursorLoaderSettings},
+    cur::asset::{StaticCursor, StaticCursorLoaderSettings},
+    handle::CursorHandle,
+    variant::DEFAULT_CURSOR_SIZE,
+    xcursor::asset::{
+        animated_cursor_from_xcursor, decode_bytes, static_cursor_from_xcursor, XCursorLoaderError,
+    },
+};
+
+use super::{cursor_name_aliases, IndexTheme, InstallInf, CURSOR_NAME_ALIASES};
+
+pub struct CursorThemeAssetPlugin;
+
+impl Plugin for CursorThemeAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CursorTheme>()
+            .init_asset_loader::<XCursorThemeLoader>()
+            .init_asset_loader::<WindowsCursorSchemeLoader>()
+            .register_asset_reflect::<CursorTheme>();
+    }
+}
+
+/// A set of cursors, keyed by name, loaded from an X11 cursor theme or a
+/// Windows cursor scheme.
+#[derive(Asset, Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct CursorTheme {
+    /// The name of the theme.
+    pub name: Option<String>,
+    /// The names of the themes this theme inherits cursors from, directly or
+    /// through other themes, in the order they were searched.
+    ///
+    /// Inherited cursors are already in `cursors`.
+    pub inherits: Vec<String>,
+    /// The cursors, keyed by the name they were found under.
+    pub cursors: HashMap<String, CursorHandle>,
+}
+
+impl CursorTheme {
+    /// Returns the cursor with the given name, or with one of its aliases,
+    /// e.g. `left_ptr` for `default`.
+    pub fn get(&self, name: &str) -> Option<&CursorHandle> {
+        self.cursors.get(name).or_else(|| {
+            cursor_name_aliases(name)
+                .iter()
+                .find_map(|alias| self.cursors.get(*alias))
+        })
+    }
+}
+
+/// A loader for X11 cursor themes.
+///
+/// Load a theme's `index.theme` file. Cursors are read from the `cursors`
+/// directory next to it, by the names in [`CURSOR_NAME_ALIASES`], and each
+/// one is added as a static or animated cursor labeled asset with its name as
+/// the label. Cursors missing from the theme are looked up in the themes it
+/// inherits from, which are expected to be sibling directories.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct XCursorThemeLoader;
+
+/// A loader for Windows cursor schemes.
+///
+/// Load a scheme's `install.inf` file. Its `.cur` and `.ani` files are loaded
+/// from the same directory, with the names in
+/// [`WINDOWS_SCHEME_CURSOR_NAMES`](super::WINDOWS_SCHEME_CURSOR_NAMES).
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct WindowsCursorSchemeLoader;
+
+/// Settings for loading cursor themes with [`XCursorThemeLoader`] and
+/// [`WindowsCursorSchemeLoader`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CursorThemeLoaderSettings {
+    /// If set, only the size of each cursor that best matches this size, in
+    /// pixels, is loaded. Otherwise, every size is loaded.
+    pub preferred_size: Option<u32>,
+    /// The usage of the texture atlas images.
+    ///
+    /// Cursor images must be available in the main world for the window to
+    /// use them.
+    pub asset_usage: RenderAssetUsages,
+}
+
+impl CursorThemeLoaderSettings {
+    fn static_cursor_settings(&self) -> StaticCursorLoaderSettings {
+        StaticCursorLoaderSettings {
+            preferred_size: self.preferred_size,
+            asset_usage: self.asset_usage,
+            keep_entry_images: false,
+        }
+    }
+
+    fn animated_cursor_settings(&self) -> AnimatedCursorLoaderSettings {
+        AnimatedCursorLoaderSettings {
+            preferred_size: self.preferred_size.unwrap_or(DEFAULT_CURSOR_SIZE),
+            load_all_sizes: self.preferred_size.is_none(),
+            asset_usage: self.asset_usage,
+            keep_frame_images: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// Possible errors that can be produced by [`XCursorThemeLoader`] and
+/// [`WindowsCursorSchemeLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CursorThemeLoaderError {
+    /// An [IO](std::io) error.
+    #[error("could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [ReadAssetBytesError] error.
+    #[error("could not read theme file: {0}")]
+    ReadAssetBytesError(#[from] ReadAssetBytesError),
+    /// A [ParseAssetPathError] error.
+    #[error("invalid theme file path: {0}")]
+    ParseAssetPathError(#[from] ParseAssetPathError),
+    /// An [XCursorLoaderError] error.
+    #[error("could not load cursor {name}: {error}")]
+    XCursorLoaderError {
+        name: String,
+        error: XCursorLoaderError,
+    },
+    #[error("install.inf has no cursor scheme")]
+    MissingScheme,
+}
+
+/// Reads the file at `path`, or returns `None` if it doesn't exist.
+async fn read_optional(
+    load_context: &mut LoadContext<'_>,
+    path: AssetPath<'static>,
+) -> Result<Option<Vec<u8>>, CursorThemeLoaderError> {
+    match load_context.read_asset_bytes(path).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl AssetLoader for XCursorThemeLoader {
+    type Asset = CursorTheme;
+    type Settings = CursorThemeLoaderSettings;
+    type Error = CursorThemeLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let index = IndexTheme::parse(&String::from_utf8_lossy(&bytes));
+
+        let mut theme = CursorTheme {
+            name: index.name.clone(),
+            ..Default::default()
+        };
+
+        // Search this theme first and then the themes it inherits from,
+        // breadth first, skipping themes that were already searched. Themes
+        // are named by their directory, so this one is already searched.
+        let mut visited = load_context
+            .path()
+            .path()
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut queue = VecDeque::from([(load_context.path().clone(), index)]);
+        while let Some((path, index)) = queue.pop_front() {
+            for names in CURSOR_NAME_ALIASES {
+                if names.iter().any(|name| theme.cursors.contains_key(*name)) {
+                    continue;
+                }
+                for name in *names {
+                    let cursor_path = path.resolve_embed_str(&format!("cursors/{}", name))?;
+                    let Some(bytes) = read_optional(load_context, cursor_path).await? else {
+                        continue;
+                    };
+
+                    let handle = load_cursor(load_context, name, bytes, settings)?;
+                    theme.cursors.insert(name.to_string(), handle);
+                    break;
+                }
+            }
+
+            for parent in &index.inherits {
+                if !visited.insert(parent.clone()) {
+                    continue;
+                }
+                theme.inherits.push(parent.clone());
+                let parent_path = path.resolve_embed_str(&format!("../{}/index.theme", parent))?;
+                let parent_index = read_optional(load_context, parent_path.clone())
+                    .await?
+                    .map(|bytes| IndexTheme::parse(&String::from_utf8_lossy(&bytes)))
+                    .unwrap_or_default();
+                queue.push_back((parent_path, parent_index));
+            }
+        }
+
+        Ok(theme)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme"]
+    }
+}
+
+/// Decodes an Xcursor file and adds it as a labeled asset named `name`, as an
+/// animated cursor if it has more than one frame and as a static cursor
+/// otherwise.
+fn load_cursor(
+    load_context: &mut LoadContext<'_>,
+    name: &str,
+    bytes: Vec<u8>,
+    settings: &CursorThemeLoaderSettings,
+) -> Result<CursorHandle, CursorThemeLoaderError> {
+    let error = |error: XCursorLoaderError| CursorThemeLoaderError::XCursorLoaderError {
+        name: name.to_string(),
+        error,
+    };
+
+    let c = decode_bytes(bytes).map_err(error)?;
+    let label_prefix = format!("{}/", name);
+
+    Ok(if c.is_animated() {
+        let cursor = animated_cursor_from_xcursor(
+            &c,
+            &settings.animated_cursor_settings(),
+            load_context,
+            &label_prefix,
+        )
+        .map_err(error)?;
+        load_context
+            .add_labeled_asset(name.to_string(), cursor)
+            .into()
+    } else {
+        let cursor = static_cursor_from_xcursor(
+            &c,
+            &settings.static_cursor_settings(),
+            load_context,
+            &label_prefix,
+        )
+        .map_err(error)?;
+        load_context
+            .add_labeled_asset(name.to_string(), cursor)
+            .into()
+    })
+}
+
+impl AssetLoader for WindowsCursorSchemeLoader {
+    type Asset = CursorTheme;
+    type Settings = CursorThemeLoaderSettings;
+    type Error = CursorThemeLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let inf = InstallInf::parse(&String::from_utf8_lossy(&bytes))
+            .ok_or(CursorThemeLoaderError::MissingScheme)?;
+
+        let mut cursors = HashMap::new();
+        for (name, file_name) in inf.cursors {
+            let path = load_context.path().resolve_embed_str(&file_name)?;
+            let extension = file_name
+                .rsplit_once('.')
+                .map(|(_, extension)| extension.to_ascii_lowercase());
+
+            let handle = match extension.as_deref() {
+                Some("cur") => {
+                    let static_settings = settings.static_cursor_settings();
+                    load_context
+                        .load_builder()
+                        .with_settings(move |s: &mut StaticCursorLoaderSettings| {
+                            *s = static_settings.clone();
+                        })
+                        .load::<StaticCursor>(path)
+                        .into()
+                }
+                Some("ani") => {
+                    let animated_settings = settings.animated_cursor_settings();
+                    load_context
+                        .load_builder()
+                        .with_settings(move |s: &mut AnimatedCursorLoaderSettings| {
+                            *s = animated_settings.clone();
+                        })
+                        .load::<AnimatedCursor>(path)
+                        .into()
+                }
+                // Schemes can't use other files as cursors.
+                _ => continue,
+            };
+            cursors.insert(name, handle);
+        }
+
+        Ok(CursorTheme {
+            name: inf.name,
+            inherits: Vec::new(),
+            cursors,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["inf"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::testing::{ani_bytes, create_app, cur_bytes, load_with_settings, xcursor_bytes};
+
+    use super::*;
+
+    #[test]
+    fn test_load_xcursor_theme() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset_text(
+            Path::new("themes/child/index.theme"),
+            "[Icon Theme]\nName=Child\nInherits=parent\n",
+        );
+        dir.insert_asset(
+            Path::new("themes/child/cursors/left_ptr"),
+            xcursor_bytes(&[(16, 16, (1, 1), 0, 0xff00_0000)]),
+        );
+        // Inherits itself back, which must not be searched again.
+        dir.insert_asset_text(
+            Path::new("themes/parent/index.theme"),
+            "[Icon Theme]\nInherits=child\n",
+        );
+        dir.insert_asset(
+            Path::new("themes/parent/cursors/default"),
+            xcursor_bytes(&[(16, 16, (1, 1), 0, 0xffff_ffff)]),
+        );
+        dir.insert_asset(
+            Path::new("themes/parent/cursors/watch"),
+            xcursor_bytes(&[
+                (16, 16, (1, 1), 50, 0xff00_0000),
+                (16, 16, (1, 1), 50, 0xffff_ffff),
+            ]),
+        );
+
+        let handle = load_with_settings::<CursorTheme, CursorThemeLoaderSettings>(
+            &mut app,
+            "themes/child/index.theme",
+            |_| {},
+        )
+        .unwrap();
+        let themes = app.world().resource::<Assets<CursorTheme>>();
+        let theme = themes.get(&handle).unwrap();
+
+        assert_eq!(theme.name.as_deref(), Some("Child"));
+        assert_eq!(theme.inherits, vec!["parent"]);
+        // The theme's own cursor is used over the inherited alias.
+        assert_eq!(theme.cursors.len(), 2);
+        assert!(matches!(
+            theme.cursors.get("left_ptr"),
+            Some(CursorHandle::Static(_))
+        ));
+        assert!(matches!(
+            theme.get("default"),
+            Some(CursorHandle::Static(_))
+        ));
+        assert!(matches!(theme.get("wait"), Some(CursorHandle::Animated(_))));
+        assert!(theme.get("pointer").is_none());
+    }
+
+    #[test]
+    fn test_load_windows_cursor_scheme() {
+        let (mut app, dir) = create_app();
+        dir.insert_asset_text(
+            Path::new("scheme/install.inf"),
+            r#"[Scheme.Reg]
+HKCU,"Control Panel\Cursors\Schemes","%SCHEME_NAME%",,"%10%\%CUR_DIR%\%pointer%,,,%10%\%CUR_DIR%\%busy%,%10%\%CUR_DIR%\%cross%"
+
+[Strings]
+CUR_DIR = "Cursors\Scheme"
+SCHEME_NAME = "Scheme"
+pointer = "Normal.cur"
+busy = "Busy.ani"
+cross = "Cross.png"
+"#,
+        );
+        dir.insert_asset(Path::new("scheme/Normal.cur"), cur_bytes(&[32]));
+        dir.insert_asset(Path::new("scheme/Busy.ani"), ani_bytes(2, &[32], 6));
+
+        let handle = load_with_settings::<CursorTheme, CursorThemeLoaderSettings>(
+            &mut app,
+            "scheme/install.inf",
+            |_| {},
+        )
+        .unwrap();
+        let themes = app.world().resource::<Assets<CursorTheme>>();
+        let theme = themes.get(&handle).unwrap();
+
+        assert_eq!(theme.name.as_deref(), Some("Scheme"));
+        // Files other than .cur and .ani are skipped.
+        assert_eq!(theme.cursors.len(), 2);
+        let Some(CursorHandle::Static(default)) = theme.get("default") else {
+            panic!("expected a static default cursor");
+        };
+        assert_eq!(
+            default.path().map(|path| path.to_string()).as_deref(),
+            Some("scheme/Normal.cur")
+        );
+        let Some(CursorHandle::Animated(wait)) = theme.get("wait") else {
+            panic!("expected an animated wait cursor");
+        };
+        assert_eq!(
+            wait.path().map(|path| path.to_string()).as_deref(),
+            Some("scheme/Busy.ani")
+        );
+    }
+}
@@ -0,0 +1,306 @@
+pub mod asset;
+pub mod system;
+
+use std::collections::HashMap;
+
+/// Groups of cursor names that refer to the same cursor.
+///
+/// The first name of each group is the CSS name, which the freedesktop.org
+/// cursor spec also uses, followed by the legacy X11 names that themes often
+/// provide as symlinks instead.
+pub const CURSOR_NAME_ALIASES: &[&[&str]] = &[
+    &["default", "left_ptr", "arrow", "top_left_arrow"],
+    &["context-menu"],
+    &["help", "question_arrow", "whats_this", "left_ptr_help"],
+    &["pointer", "hand2", "hand1", "hand", "pointing_hand"],
+    &["progress", "left_ptr_watch", "half-busy"],
+    &["wait", "watch"],
+    &["cell", "plus"],
+    &["crosshair", "cross", "tcross"],
+    &["text", "xterm", "ibeam"],
+    &["vertical-text"],
+    &["alias", "dnd-link", "link"],
+    &["copy", "dnd-copy"],
+    &["move", "fleur", "size_all"],
+    &["no-drop", "dnd-no-drop"],
+    &["not-allowed", "crossed_circle", "forbidden", "circle"],
+    &["grab", "openhand"],
+    &["grabbing", "closedhand"],
+    &["e-resize", "right_side"],
+    &["n-resize", "top_side"],
+    &["ne-resize", "top_right_corner"],
+    &["nw-resize", "top_left_corner"],
+    &["s-resize", "bottom_side"],
+    &["se-resize", "bottom_right_corner"],
+    &["sw-resize", "bottom_left_corner"],
+    &["w-resize", "left_side"],
+    &[
+        "ew-resize",
+        "sb_h_double_arrow",
+        "h_double_arrow",
+        "size_hor",
+    ],
+    &[
+        "ns-resize",
+        "sb_v_double_arrow",
+        "v_double_arrow",
+        "size_ver",
+    ],
+    &["nesw-resize", "fd_double_arrow", "size_bdiag"],
+    &["nwse-resize", "bd_double_arrow", "size_fdiag"],
+    &["col-resize", "split_h"],
+    &["row-resize", "split_v"],
+    &["all-scroll"],
+    &["zoom-in"],
+    &["zoom-out"],
+    &["pencil"],
+    &["up-arrow", "center_ptr"],
+    &["pin"],
+    &["person"],
+];
+
+/// The cursor names of a Windows cursor scheme, in the order of the cursor
+/// paths in its registry value.
+///
+/// These are the roles `Arrow`, `Help`, `AppStarting`, `Wait`, `Crosshair`,
+/// `IBeam`, `NWPen`, `No`, `SizeNS`, `SizeWE`, `SizeNWSE`, `SizeNESW`,
+/// `SizeAll`, `UpArrow`, `Hand`, `Pin` and `Person`, named like the cursors of
+/// X11 themes.
+pub const WINDOWS_SCHEME_CURSOR_NAMES: &[&str] = &[
+    "default",
+    "help",
+    "progress",
+    "wait",
+    "crosshair",
+    "text",
+    "pencil",
+    "not-allowed",
+    "ns-resize",
+    "ew-resize",
+    "nwse-resize",
+    "nesw-resize",
+    "move",
+    "up-arrow",
+    "pointer",
+    "pin",
+    "person",
+];
+
+/// Returns the names that refer to the same cursor as `name`, including
+/// `name` itself, or an empty slice if `name` has no known aliases.
+pub fn cursor_name_aliases(name: &str) -> &'static [&'static str] {
+    CURSOR_NAME_ALIASES
+        .iter()
+        .find(|names| names.contains(&name))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// The contents of an X11 cursor theme's `index.theme` file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IndexTheme {
+    /// The `Name` of the theme.
+    pub name: Option<String>,
+    /// The names of the themes in `Inherits`, in order.
+    pub inherits: Vec<String>,
+}
+
+impl IndexTheme {
+    /// Parses the `[Icon Theme]` section of an `index.theme` file. Other
+    /// sections and unknown keys are ignored.
+    pub fn parse(s: &str) -> Self {
+        let mut theme = IndexTheme::default();
+
+        for (section, key, value) in ini_entries(s) {
+            if !section.eq_ignore_ascii_case("Icon Theme") {
+                continue;
+            }
+            match key {
+                "Name" => theme.name = Some(value.to_string()),
+                "Inherits" => {
+                    theme.inherits = value
+                        .split([',', ';'])
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        theme
+    }
+}
+
+/// A Windows cursor scheme, as installed by an `install.inf` file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InstallInf {
+    /// The name of the scheme.
+    pub name: Option<String>,
+    /// The file name of each cursor, keyed by its name in
+    /// [`WINDOWS_SCHEME_CURSOR_NAMES`].
+    pub cursors: HashMap<String, String>,
+}
+
+impl InstallInf {
+    /// Parses an `install.inf` file, or returns `None` if it doesn't add a
+    /// cursor scheme to the registry.
+    ///
+    /// The scheme is the `HKCU,"Control Panel\Cursors\Schemes"` registry
+    /// entry, whose value is a comma separated list of cursor paths.
+    /// `%string%` references are expanded from the `[Strings]` section and
+    /// only the file name of each path is kept, since the cursors are expected
+    /// to be next to the `install.inf` file.
+    pub fn parse(s: &str) -> Option<Self> {
+        let strings = ini_entries(s)
+            .filter(|(section, _, _)| section.eq_ignore_ascii_case("Strings"))
+            .map(|(_, key, value)| (key.to_ascii_lowercase(), unquote(value).to_string()))
+            .collect::<HashMap<_, _>>();
+
+        let fields = s.lines().find_map(|line| {
+            let fields = split_fields(line);
+            let is_scheme = fields.len() >= 5
+                && fields[0].eq_ignore_ascii_case("HKCU")
+                && unquote(&fields[1]).eq_ignore_ascii_case(r"Control Panel\Cursors\Schemes");
+            is_scheme.then_some(fields)
+        })?;
+
+        let name = expand(unquote(&fields[2]), &strings);
+        let paths = expand(unquote(&fields[4]), &strings);
+
+        let cursors = WINDOWS_SCHEME_CURSOR_NAMES
+            .iter()
+            .zip(paths.split(','))
+            .filter_map(|(name, path)| {
+                let file_name = path.trim().rsplit(['\\', '/']).next()?;
+                (!file_name.is_empty()).then(|| (name.to_string(), file_name.to_string()))
+            })
+            .collect();
+
+        Some(InstallInf {
+            name: (!name.is_empty()).then_some(name),
+            cursors,
+        })
+    }
+}
+
+/// Returns the `(section, key, value)` of each `key = value` line of an INI
+/// style file, skipping comments.
+fn ini_entries(s: &str) -> impl Iterator<Item = (&str, &str, &str)> {
+    let mut section = "";
+    s.lines().filter_map(move |line| {
+        let line = line.trim();
+        if line.starts_with(['#', ';']) {
+            return None;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim();
+            return None;
+        }
+        let (key, value) = line.split_once('=')?;
+        Some((section, key.trim(), value.trim()))
+    })
+}
+
+/// Splits a line of an `.inf` file into its comma separated fields, keeping
+/// commas inside quotes.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.trim().chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                fields.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        fields.last_mut().unwrap().push(c);
+    }
+    fields
+        .iter()
+        .map(|field| field.trim().to_string())
+        .collect()
+}
+
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Expands `%string%` references. Unknown strings, such as `%10%` for the
+/// Windows directory, expand to nothing.
+fn expand(s: &str, strings: &HashMap<String, String>) -> String {
+    let mut expanded = String::new();
+    let mut parts = s.split('%');
+    if let Some(first) = parts.next() {
+        expanded.push_str(first);
+    }
+    let mut is_reference = true;
+    for part in parts {
+        if is_reference {
+            if let Some(value) = strings.get(&part.to_ascii_lowercase()) {
+                expanded.push_str(value);
+            }
+        } else {
+            expanded.push_str(part);
+        }
+        is_reference = !is_reference;
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_index_theme() {
+        let theme = IndexTheme::parse(
+            "# A theme\n\
+             [Icon Theme]\n\
+             Name = Breeze\n\
+             Comment=KDE Plasma cursor theme\n\
+             Inherits=breeze_cursors, Adwaita\n",
+        );
+
+        assert_eq!(theme.name.as_deref(), Some("Breeze"));
+        assert_eq!(theme.inherits, vec!["breeze_cursors", "Adwaita"]);
+    }
+
+    #[test]
+    fn test_parse_install_inf() {
+        let inf = InstallInf::parse(
+            r#"[Version]
+signature="$CHICAGO$"
+
+[Scheme.Reg]
+HKCU,"Control Panel\Cursors\Schemes","%SCHEME_NAME%",,"%10%\%CUR_DIR%\%pointer%,%10%\%CUR_DIR%\%help%,,%10%\%CUR_DIR%\%busy%"
+
+[Strings]
+CUR_DIR = "Cursors\Hourglass"
+SCHEME_NAME = "Hourglass"
+Pointer = "Normal.cur"
+help = "Help.cur"
+busy = "Busy.ani"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(inf.name.as_deref(), Some("Hourglass"));
+        assert_eq!(inf.cursors.len(), 3);
+        assert_eq!(inf.cursors["default"], "Normal.cur");
+        assert_eq!(inf.cursors["help"], "Help.cur");
+        assert_eq!(inf.cursors["wait"], "Busy.ani");
+    }
+
+    #[test]
+    fn test_cursor_name_aliases() {
+        assert!(cursor_name_aliases("hand2").contains(&"pointer"));
+        assert!(cursor_name_aliases("left_ptr").contains(&"default"));
+        assert!(cursor_name_aliases("unknown").is_empty());
+    }
+}
@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_reflect::prelude::*;
+use bevy_window::{CursorIcon, CustomCursor, SystemCursorIcon};
+
+use crate::{
+    ani::asset::AnimatedCursor,
+    builder::CustomCursorImageBuilder,
+    cur::asset::StaticCursor,
+    handle::CursorHandle,
+    player::{play_animated_cursors, AnimatedCursorPlayer},
+};
+
+use super::asset::CursorTheme;
+
+/// The cursor name of each [`SystemCursorIcon`] in cursor themes.
+pub const SYSTEM_CURSOR_NAMES: &[(SystemCursorIcon, &str)] = &[
+    (SystemCursorIcon::Default, "default"),
+    (SystemCursorIcon::ContextMenu, "context-menu"),
+    (SystemCursorIcon::Help, "help"),
+    (SystemCursorIcon::Pointer, "pointer"),
+    (SystemCursorIcon::Progress, "progress"),
+    (SystemCursorIcon::Wait, "wait"),
+    (SystemCursorIcon::Cell, "cell"),
+    (SystemCursorIcon::Crosshair, "crosshair"),
+    (SystemCursorIcon::Text, "text"),
+    (SystemCursorIcon::VerticalText, "vertical-text"),
+    (SystemCursorIcon::Alias, "alias"),
+    (SystemCursorIcon::Copy, "copy"),
+    (SystemCursorIcon::Move, "move"),
+    (SystemCursorIcon::NoDrop, "no-drop"),
+    (SystemCursorIcon::NotAllowed, "not-allowed"),
+    (SystemCursorIcon::Grab, "grab"),
+    (SystemCursorIcon::Grabbing, "grabbing"),
+    (SystemCursorIcon::EResize, "e-resize"),
+    (SystemCursorIcon::NResize, "n-resize"),
+    (SystemCursorIcon::NeResize, "ne-resize"),
+    (SystemCursorIcon::NwResize, "nw-resize"),
+    (SystemCursorIcon::SResize, "s-resize"),
+    (SystemCursorIcon::SeResize, "se-resize"),
+    (SystemCursorIcon::SwResize, "sw-resize"),
+    (SystemCursorIcon::WResize, "w-resize"),
+    (SystemCursorIcon::EwResize, "ew-resize"),
+    (SystemCursorIcon::NsResize, "ns-resize"),
+    (SystemCursorIcon::NeswResize, "nesw-resize"),
+    (SystemCursorIcon::NwseResize, "nwse-resize"),
+    (SystemCursorIcon::ColResize, "col-resize"),
+    (SystemCursorIcon::RowResize, "row-resize"),
+    (SystemCursorIcon::AllScroll, "all-scroll"),
+    (SystemCursorIcon::ZoomIn, "zoom-in"),
+    (SystemCursorIcon::ZoomOut, "zoom-out"),
+];
+
+/// A plugin that replaces the [`CursorIcon::System`] icons of windows with the
+/// custom cursors of the [`SystemCursorTheme`] resource.
+///
+/// Add [`AnimatedCursorPlayerPlugin`](crate::player::AnimatedCursorPlayerPlugin)
+/// too to play animated cursors. Icons are replaced in [`Update`], before the
+/// players advance, so icons set after that are replaced on the next update.
+pub struct SystemCursorThemePlugin;
+
+impl Plugin for SystemCursorThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SystemCursorTheme>()
+            .register_type::<ThemedSystemCursor>()
+            .init_resource::<SystemCursorTheme>()
+            .add_systems(
+                Update,
+                apply_system_cursor_theme.before(play_animated_cursors),
+            );
+    }
+}
+
+/// A cursor that replaces a [`SystemCursorIcon`].
+#[derive(Clone, Debug, Eq, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub struct ThemedCursor {
+    /// The cursor to display.
+    pub cursor: CursorHandle,
+    /// The atlas index to display. Animated cursors start at this index and
+    /// are then played with an [`AnimatedCursorPlayer`].
+    pub atlas_index: usize,
+}
+
+/// Maps [`SystemCursorIcon`]s to custom cursors.
+///
+/// Whenever a window's cursor icon is set to a [`CursorIcon::System`] icon
+/// that has a cursor in this theme, e.g. by UI code, it is replaced by a
+/// [`CursorIcon::Custom`] icon of that cursor. Icons without a cursor, or
+/// whose cursor hasn't loaded yet, are left alone.
+#[derive(Clone, Debug, Default, Reflect, Resource)]
+#[reflect(Debug, Default, Resource)]
+pub struct SystemCursorTheme {
+    /// The cursor of each system cursor icon.
+    pub cursors: HashMap<SystemCursorIcon, ThemedCursor>,
+}
+
+impl SystemCursorTheme {
+    /// Creates a theme from the cursors of a [`CursorTheme`] that are named
+    /// like system cursor icons, see [`SYSTEM_CURSOR_NAMES`].
+    pub fn from_cursor_theme(theme: &CursorTheme) -> Self {
+        Self {
+            cursors: SYSTEM_CURSOR_NAMES
+                .iter()
+                .filter_map(|(icon, name)| {
+                    let cursor = theme.get(name)?.clone();
+                    Some((
+                        *icon,
+                        ThemedCursor {
+                            cursor,
+                            atlas_index: 0,
+                        },
+                    ))
+                })
+                .collect(),
+        }
+    }
+
+    /// Sets the cursor of `icon`, returning the previous one.
+    pub fn insert(
+        &mut self,
+        icon: SystemCursorIcon,
+        cursor: impl Into<CursorHandle>,
+        atlas_index: usize,
+    ) -> Option<ThemedCursor> {
+        self.cursors.insert(
+            icon,
+            ThemedCursor {
+                cursor: cursor.into(),
+                atlas_index,
+            },
+        )
+    }
+
+    /// Returns the cursor of `icon`, if any.
+    pub fn get(&self, icon: SystemCursorIcon) -> Option<&ThemedCursor> {
+        self.cursors.get(&icon)
+    }
+
+    /// Removes the cursor of `icon`, returning it.
+    pub fn remove(&mut self, icon: SystemCursorIcon) -> Option<ThemedCursor> {
+        self.cursors.remove(&icon)
+    }
+}
+
+/// Marks a window whose cursor icon was replaced by [`SystemCursorTheme`],
+/// with the system cursor icon that was replaced.
+///
+/// The [`AnimatedCursorPlayer`] of a window with this component is managed by
+/// the theme. It keeps playing when the icon is set to the same system cursor
+/// icon again, and is removed when the icon changes to one that isn't
+/// animated or to another custom cursor.
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq)]
+pub struct ThemedSystemCursor(pub SystemCursorIcon);
+
+fn apply_system_cursor_theme(
+    mut commands: Commands,
+    theme: Res<SystemCursorTheme>,
+    static_cursors: Res<Assets<StaticCursor>>,
+    animated_cursors: Res<Assets<AnimatedCursor>>,
+    mut query: Query<(
+        Entity,
+        &mut CursorIcon,
+        Option<&ThemedSystemCursor>,
+        Option<&AnimatedCursorPlayer>,
+    )>,
+) {
+    for (entity, mut cursor_icon, themed, player) in &mut query {
+        let CursorIcon::System(icon) = *cursor_icon else {
+            let Some(themed) = themed else {
+                continue;
+            };
+
+            // The icon was replaced by another custom cursor, so the theme no
+            // longer manages the window's player, unless the new cursor is
+            // the themed one or is played by the player.
+            let image = |handle: &CursorHandle| match handle {
+                CursorHandle::Static(handle) => static_cursors.get(handle).map(|c| &c.image),
+                CursorHandle::Animated(handle) => animated_cursors.get(handle).map(|c| &c.image),
+            };
+            let displays = |cursor: Option<&Handle<Image>>| {
+                matches!(
+                    (&*cursor_icon, cursor),
+                    (CursorIcon::Custom(CustomCursor::Image(image)), Some(cursor))
+                        if image.handle == *cursor
+                )
+            };
+
+            if displays(theme.get(themed.0).and_then(|t| image(&t.cursor))) {
+                continue;
+            }
+
+            let mut entity = commands.entity(entity);
+            entity.remove::<ThemedSystemCursor>();
+            if !displays(player.and_then(|p| animated_cursors.get(&p.cursor).map(|c| &c.image))) {
+                entity.remove::<AnimatedCursorPlayer>();
+            }
+            continue;
+        };
+
+        let Some(themed_cursor) = theme.get(icon) else {
+            // The system icon is displayed as is, so stop animating the
+            // previous themed cursor.
+            if themed.is_some() {
+                commands
+                    .entity(entity)
+                    .remove::<(ThemedSystemCursor, AnimatedCursorPlayer)>();
+            }
+            continue;
+        };
+
+        let is_themed = themed == Some(&ThemedSystemCursor(icon));
+        let atlas_index = themed_cursor.atlas_index;
+        match &themed_cursor.cursor {
+            CursorHandle::Static(handle) => {
+                let Some(c) = static_cursors.get(handle) else {
+                    continue;
+                };
+
+                *cursor_icon = CursorIcon::Custom(
+                    CustomCursorImageBuilder::from_static_cursor(c, Some(atlas_index)).build(),
+                );
+
+                if !is_themed {
+                    let mut entity = commands.entity(entity);
+                    if themed.is_some() {
+                        entity.remove::<AnimatedCursorPlayer>();
+                    }
+                    entity.insert(ThemedSystemCursor(icon));
+                }
+            }
+            CursorHandle::Animated(handle) => {
+                // The icon was set to the themed icon again, which the player
+                // displays at its current frame rather than restarting.
+                if is_themed && player.is_some_and(|player| player.cursor == *handle) {
+                    continue;
+                }
+
+                let Some(c) = animated_cursors.get(handle) else {
+                    continue;
+                };
+
+                *cursor_icon = CursorIcon::Custom(
+                    CustomCursorImageBuilder::from_animated_cursor(c, Some(atlas_index)).build(),
+                );
+
+                commands.entity(entity).insert((
+                    ThemedSystemCursor(icon),
+                    AnimatedCursorPlayer::new(handle.clone()),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use bevy_time::{TimePlugin, TimeUpdateStrategy};
+
+    use crate::{
+        ani::asset::AnimatedCursorLoaderSettings,
+        cur::asset::StaticCursorLoaderSettings,
+        player::AnimatedCursorPlayerPlugin,
+        testing::{ani_bytes, create_app, cur_bytes, load_with_settings},
+    };
+
+    use super::*;
+
+    /// Creates an app whose theme has a static `Pointer` cursor and an
+    /// animated `Wait` cursor of 3 frames of 100 ms each, and a window entity
+    /// with the `Default` icon.
+    fn setup() -> (App, Entity) {
+        let (mut app, dir) = create_app();
+        app.add_plugins((
+            TimePlugin,
+            AnimatedCursorPlayerPlugin,
+            SystemCursorThemePlugin,
+        ));
+        dir.insert_asset(Path::new("pointer.cur"), cur_bytes(&[32]));
+        dir.insert_asset(Path::new("wait.ani"), ani_bytes(3, &[32], 6));
+
+        let pointer = load_with_settings::<StaticCursor, StaticCursorLoaderSettings>(
+            &mut app,
+            "pointer.cur",
+            |_| {},
+        )
+        .unwrap();
+        let wait = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "wait.ani",
+            |_| {},
+        )
+        .unwrap();
+
+        let mut theme = app.world_mut().resource_mut::<SystemCursorTheme>();
+        theme.insert(SystemCursorIcon::Pointer, pointer, 0);
+        theme.insert(SystemCursorIcon::Wait, wait, 0);
+
+        let entity = app
+            .world_mut()
+            .spawn(CursorIcon::System(SystemCursorIcon::Default))
+            .id();
+
+        (app, entity)
+    }
+
+    /// Sets the window's icon and updates `app` after `millis` milliseconds.
+    fn update(app: &mut App, entity: Entity, icon: Option<CursorIcon>, millis: u64) {
+        if let Some(icon) = icon {
+            app.world_mut().entity_mut(entity).insert(icon);
+        }
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            millis,
+        )));
+        app.update();
+    }
+
+    /// Returns the image and atlas index of the window's custom cursor.
+    fn custom_image(app: &App, entity: Entity) -> (Handle<Image>, Option<usize>) {
+        let Some(CursorIcon::Custom(CustomCursor::Image(image))) =
+            app.world().get::<CursorIcon>(entity)
+        else {
+            panic!("expected a custom cursor image");
+        };
+        (
+            image.handle.clone(),
+            image.texture_atlas.as_ref().map(|atlas| atlas.index),
+        )
+    }
+
+    fn themed(app: &App, entity: Entity) -> Option<ThemedSystemCursor> {
+        app.world().get::<ThemedSystemCursor>(entity).copied()
+    }
+
+    fn has_player(app: &App, entity: Entity) -> bool {
+        app.world().get::<AnimatedCursorPlayer>(entity).is_some()
+    }
+
+    #[test]
+    fn test_static_cursor() {
+        let (mut app, entity) = setup();
+
+        // Icons without a cursor are left alone.
+        update(&mut app, entity, None, 0);
+        assert_eq!(
+            app.world().get::<CursorIcon>(entity),
+            Some(&CursorIcon::System(SystemCursorIcon::Default))
+        );
+        assert_eq!(themed(&app, entity), None);
+
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Pointer)),
+            0,
+        );
+        assert_eq!(
+            themed(&app, entity),
+            Some(ThemedSystemCursor(SystemCursorIcon::Pointer))
+        );
+        assert_eq!(custom_image(&app, entity).1, Some(0));
+
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Help)),
+            0,
+        );
+        assert_eq!(
+            app.world().get::<CursorIcon>(entity),
+            Some(&CursorIcon::System(SystemCursorIcon::Help))
+        );
+        assert_eq!(themed(&app, entity), None);
+    }
+
+    #[test]
+    fn test_animated_cursor() {
+        let (mut app, entity) = setup();
+
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Wait)),
+            0,
+        );
+        assert!(has_player(&app, entity));
+        assert_eq!(custom_image(&app, entity).1, Some(0));
+
+        update(&mut app, entity, None, 0);
+        update(&mut app, entity, None, 150);
+        assert_eq!(custom_image(&app, entity).1, Some(1));
+
+        // Setting the same icon again keeps playing from the current frame.
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Wait)),
+            0,
+        );
+        assert_eq!(custom_image(&app, entity).1, Some(1));
+        assert_eq!(
+            themed(&app, entity),
+            Some(ThemedSystemCursor(SystemCursorIcon::Wait))
+        );
+
+        // An icon without a cursor isn't replaced by the player's frame.
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Help)),
+            0,
+        );
+        assert_eq!(
+            app.world().get::<CursorIcon>(entity),
+            Some(&CursorIcon::System(SystemCursorIcon::Help))
+        );
+        assert!(!has_player(&app, entity));
+        assert_eq!(themed(&app, entity), None);
+
+        // Static cursors stop the animation too.
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Wait)),
+            0,
+        );
+        assert!(has_player(&app, entity));
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Pointer)),
+            0,
+        );
+        assert!(!has_player(&app, entity));
+        assert_eq!(
+            themed(&app, entity),
+            Some(ThemedSystemCursor(SystemCursorIcon::Pointer))
+        );
+    }
+
+    #[test]
+    fn test_other_custom_cursor() {
+        let (mut app, entity) = setup();
+
+        update(
+            &mut app,
+            entity,
+            Some(CursorIcon::System(SystemCursorIcon::Wait)),
+            0,
+        );
+        assert!(has_player(&app, entity));
+
+        let pointer = match &app
+            .world()
+            .resource::<SystemCursorTheme>()
+            .get(SystemCursorIcon::Pointer)
+            .unwrap()
+            .cursor
+        {
+            CursorHandle::Static(handle) => handle.clone(),
+            CursorHandle::Animated(_) => unreachable!(),
+        };
+        let icon = CursorIcon::Custom(
+            CustomCursorImageBuilder::from_static_cursor(
+                app.world()
+                    .resource::<Assets<StaticCursor>>()
+                    .get(&pointer)
+                    .unwrap(),
+                None,
+            )
+            .build(),
+        );
+
+        // A custom cursor set by the app is left alone, and the theme's player
+        // no longer plays over it.
+        update(&mut app, entity, Some(icon.clone()), 0);
+        update(&mut app, entity, None, 150);
+        assert_eq!(app.world().get::<CursorIcon>(entity), Some(&icon));
+        assert!(!has_player(&app, entity));
+        assert_eq!(themed(&app, entity), None);
+    }
+}
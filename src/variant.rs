@@ -0,0 +1,106 @@
+use bevy_math::UVec2;
+use bevy_reflect::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The size, in pixels, that cursors are displayed at by default when a file
+/// contains several sizes to choose from.
+///
+/// This is the standard cursor size on Windows at 100% scaling.
+pub const DEFAULT_CURSOR_SIZE: u32 = 32;
+
+/// A resolution variant of a cursor.
+///
+/// Cursor files can contain the same cursor at several sizes. Each variant
+/// occupies a contiguous range of atlas indices starting at `atlas_offset`,
+/// with one index per frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", reflect(Deserialize, Serialize))]
+pub struct CursorVariant {
+    /// The size of the variant's images, in pixels.
+    pub size: UVec2,
+    /// The atlas index of the variant's first frame.
+    pub atlas_offset: usize,
+}
+
+/// Returns the variant that best matches `size`, in pixels.
+///
+/// The smallest variant that is at least `size` pixels wide and high is
+/// preferred, since scaling an image down looks better than scaling it up. If
+/// every variant is smaller than `size`, the largest variant is returned.
+pub fn best_variant(variants: &[CursorVariant], size: u32) -> Option<&CursorVariant> {
+    best_size_index(variants.iter().map(|v| v.size.max_element()), size).map(|i| &variants[i])
+}
+
+/// Returns the cursor size, in pixels, that suits a window with the given
+/// scale factor.
+///
+/// This is [`DEFAULT_CURSOR_SIZE`] scaled by `scale_factor`.
+pub fn size_for_scale_factor(scale_factor: f32) -> u32 {
+    (DEFAULT_CURSOR_SIZE as f32 * scale_factor).round() as u32
+}
+
+/// Returns the index of the size that best matches `size`, using the same rule
+/// as [`best_variant`].
+pub(crate) fn best_size_index(sizes: impl IntoIterator<Item = u32>, size: u32) -> Option<usize> {
+    let sizes = sizes.into_iter().enumerate().collect::<Vec<_>>();
+
+    sizes
+        .iter()
+        .filter(|(_, s)| *s >= size)
+        .min_by_key(|(_, s)| *s)
+        .or_else(|| sizes.iter().max_by_key(|(_, s)| *s))
+        .map(|(i, _)| *i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_variant() {
+        let variants = [32, 48, 64, 128]
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| CursorVariant {
+                size: UVec2::splat(size),
+                atlas_offset: i,
+            })
+            .collect::<Vec<_>>();
+
+        let best = |size| best_variant(&variants, size).map(|v| v.size.x);
+
+        assert_eq!(best(16), Some(32));
+        assert_eq!(best(32), Some(32));
+        assert_eq!(best(40), Some(48));
+        assert_eq!(best(64), Some(64));
+        assert_eq!(best(256), Some(128));
+        assert_eq!(best_variant(&[], 32), None);
+    }
+
+    #[test]
+    fn test_best_variant_tie() {
+        let variants = [UVec2::new(32, 24), UVec2::splat(32), UVec2::splat(48)]
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| CursorVariant {
+                size,
+                atlas_offset: i,
+            })
+            .collect::<Vec<_>>();
+
+        // Variants of the same size are matched in order.
+        assert_eq!(best_variant(&variants, 32).map(|v| v.atlas_offset), Some(0));
+        assert_eq!(best_variant(&variants, 33).map(|v| v.atlas_offset), Some(2));
+    }
+
+    #[test]
+    fn test_size_for_scale_factor() {
+        assert_eq!(size_for_scale_factor(1.0), DEFAULT_CURSOR_SIZE);
+        assert_eq!(size_for_scale_factor(1.5), 48);
+        assert_eq!(size_for_scale_factor(2.0), 64);
+        assert_eq!(size_for_scale_factor(1.26), 40);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use bevy_app::prelude::*;
+use bevy_asset::{io::Reader, prelude::*, AssetLoader, LoadContext};
+use bevy_image::TextureAtlasBuilderError;
+use bevy_math::UVec2;
+use bevy_reflect::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    ani::{
+        animation::Animation,
+        asset::{AnimatedCursor, AnimatedCursorLoaderSettings},
+    },
+    atlas::{build_cursor_atlas, image_from_rgba},
+    cur::asset::{StaticCursor, StaticCursorLoaderSettings},
+    variant::{best_size_index, CursorVariant},
+};
+
+use super::{
+    decoder::{DecodeError, Decoder},
+    XCursor, XCursorImage,
+};
+
+/// The extensions of Xcursor files, which are loaded as animated cursors.
+///
+/// Files in X11 cursor themes usually have no extension, so load them with a
+/// `.meta` file or rename them to use one of these.
+const EXTENSIONS: &[&str] = &["xcur", "xcursor"];
+
+/// The extensions of Xcursor files that are loaded as static cursors.
+///
+/// These are distinct from [`EXTENSIONS`] so that every extension has a
+/// single loader, like the `cur.packed` and `ani.packed` extensions of packed
+/// cursors.
+const STATIC_EXTENSIONS: &[&str] = &["cur.xcur", "cur.xcursor"];
+
+pub struct XCursorAssetPlugin;
+
+impl Plugin for XCursorAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset_loader::<XCursorStaticCursorLoader>()
+            .init_asset_loader::<XCursorAnimatedCursorLoader>();
+    }
+}
+
+/// A loader for static cursor assets from Xcursor files.
+///
+/// Each nominal size becomes an entry, using its first frame, so the atlas
+/// index of an entry is the index of its nominal size in ascending order. The
+/// settings are the same as for .CUR files.
+///
+/// Files are loaded by this loader with the `.cur.xcur` or `.cur.xcursor`
+/// extension, since the plain extensions belong to
+/// [`XCursorAnimatedCursorLoader`]. This includes cursors that aren't animated,
+/// which the plain extensions would load as an [`AnimatedCursor`] with a
+/// one-frame animation.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct XCursorStaticCursorLoader;
+
+/// A loader for animated cursor assets from Xcursor files.
+///
+/// Every nominal size becomes a variant, like the sizes of .ANI files, and the
+/// animation uses each frame's delay. The settings are the same as for .ANI
+/// files.
+///
+/// The extension, not the file's contents, selects the loader, so a `.xcur` or
+/// `.xcursor` file with a single frame per size is still loaded as an
+/// [`AnimatedCursor`] with a one-frame animation. Load it with
+/// [`XCursorStaticCursorLoader`] for a [`StaticCursor`].
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(Debug, Default)]
+pub struct XCursorAnimatedCursorLoader;
+
+/// Possible errors that can be produced by [`XCursorStaticCursorLoader`] and
+/// [`XCursorAnimatedCursorLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum XCursorLoaderError {
+    /// An [IO](std::io) error.
+    #[error("could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [DecodeError] error.
+    #[error("could not decode Xcursor: {0}")]
+    DecodeError(#[from] DecodeError),
+    #[error("Xcursor has no images")]
+    MissingImages,
+    #[error("could not create image buffer")]
+    ImageBufferError,
+    #[error("could not build texture atlas: {0}")]
+    TextureAtlasBuilderError(#[from] TextureAtlasBuilderError),
+}
+
+async fn decode(reader: &mut dyn Reader) -> Result<XCursor, XCursorLoaderError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+
+    decode_bytes(bytes)
+}
+
+/// Decodes an Xcursor file, which must have at least one image.
+pub(crate) fn decode_bytes(bytes: Vec<u8>) -> Result<XCursor, XCursorLoaderError> {
+    let c = Decoder::new(std::io::Cursor::new(bytes)).decode()?;
+    if c.images.is_empty() {
+        return Err(XCursorLoaderError::MissingImages);
+    }
+
+    Ok(c)
+}
+
+fn to_frame(image: &XCursorImage) -> Result<(bevy_image::Image, (u16, u16)), XCursorLoaderError> {
+    let frame = image_from_rgba(image.width, image.height, image.pixels.clone())
+        .ok_or(XCursorLoaderError::ImageBufferError)?;
+
+    Ok((frame, image.hotspot()))
+}
+
+/// Returns the nominal sizes to load, with the one closest to
+/// `preferred_size` first.
+fn sizes_to_load(c: &XCursor, preferred_size: u32, load_all_sizes: bool) -> Vec<u32> {
+    let mut sizes = c.nominal_sizes();
+    if let Some(i) = best_size_index(sizes.iter().copied(), preferred_size) {
+        let preferred = sizes.remove(i);
+        sizes.insert(0, preferred);
+    }
+    if !load_all_sizes {
+        sizes.truncate(1);
+    }
+    sizes
+}
+
+/// Builds a static cursor from the first frame of each nominal size of `c`,
+/// adding its atlas to `load_context` with labels that start with
+/// `label_prefix`.
+pub(crate) fn static_cursor_from_xcursor(
+    c: &XCursor,
+    settings: &StaticCursorLoaderSettings,
+    load_context: &mut LoadContext<'_>,
+    label_prefix: &str,
+) -> Result<StaticCursor, XCursorLoaderError> {
+    let sizes = match settings.preferred_size {
+        Some(size) => sizes_to_load(c, size, false),
+        None => c.nominal_sizes(),
+    };
+
+    let frames = sizes
+        .iter()
+        .map(|&size| to_frame(c.frames(size)[0]))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let variants = frames
+        .iter()
+        .enumerate()
+        .map(|(i, (image, _))| CursorVariant {
+            size: image.size(),
+            atlas_offset: i,
+        })
+        .collect();
+
+    let atlas = build_cursor_atlas(
+        load_context,
+        label_prefix,
+        frames,
+        settings.keep_entry_images,
+        settings.asset_usage,
+    )?;
+
+    Ok(StaticCursor {
+        image: atlas.image,
+        texture_atlas_layout: atlas.texture_atlas_layout,
+        hotspots: atlas.hotspots,
+        variants,
+    })
+}
+
+/// Builds an animated cursor from the frames of each nominal size of `c`,
+/// adding its atlas to `load_context` with labels that start with
+/// `label_prefix`.
+pub(crate) fn animated_cursor_from_xcursor(
+    c: &XCursor,
+    settings: &AnimatedCursorLoaderSettings,
+    load_context: &mut LoadContext<'_>,
+    label_prefix: &str,
+) -> Result<AnimatedCursor, XCursorLoaderError> {
+    let sizes = sizes_to_load(c, settings.preferred_size, settings.load_all_sizes);
+
+    // The animation plays the frames of the preferred size. Other sizes with
+    // fewer frames repeat their last frame.
+    let frame_count = c.frames(sizes[0]).len();
+
+    let mut frames = Vec::new();
+    let mut variants = Vec::new();
+    for (i, &size) in sizes.iter().enumerate() {
+        let size_frames = c.frames(size);
+
+        variants.push(CursorVariant {
+            size: UVec2::new(size_frames[0].width, size_frames[0].height),
+            atlas_offset: i * frame_count,
+        });
+
+        for f in 0..frame_count {
+            frames.push(to_frame(size_frames[f.min(size_frames.len() - 1)])?);
+        }
+    }
+
+    let atlas = build_cursor_atlas(
+        load_context,
+        label_prefix,
+        frames,
+        settings.keep_frame_images,
+        settings.asset_usage,
+    )?;
+
+    let mut animation = match settings.frame_duration {
+        Some(duration) => Animation::from_frame_durations((0..frame_count).map(|i| (i, duration))),
+        None => c.animation(sizes[0]),
+    };
+    if let Some(repeat) = settings.repeat {
+        animation.repeat = repeat;
+    }
+
+    Ok(AnimatedCursor {
+        metadata: None,
+        info: None,
+        image: atlas.image,
+        texture_atlas_layout: atlas.texture_atlas_layout,
+        hotspots: atlas.hotspots,
+        variants,
+        animation,
+        animations: BTreeMap::new(),
+    })
+}
+
+impl AssetLoader for XCursorStaticCursorLoader {
+    type Asset = StaticCursor;
+    type Settings = StaticCursorLoaderSettings;
+    type Error = XCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let c = decode(reader).await?;
+        static_cursor_from_xcursor(&c, settings, load_context, "")
+    }
+
+    fn extensions(&self) -> &[&str] {
+        STATIC_EXTENSIONS
+    }
+}
+
+impl AssetLoader for XCursorAnimatedCursorLoader {
+    type Asset = AnimatedCursor;
+    type Settings = AnimatedCursorLoaderSettings;
+    type Error = XCursorLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let c = decode(reader).await?;
+        animated_cursor_from_xcursor(&c, settings, load_context, "")
+    }
+
+    fn extensions(&self) -> &[&str] {
+        EXTENSIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        ani::asset::AnimatedCursorLoaderSettings,
+        cur::asset::StaticCursorLoaderSettings,
+        testing::{create_app, load_with_settings, xcursor_bytes},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_load_by_extension() {
+        let (mut app, dir) = create_app();
+        let bytes = xcursor_bytes(&[
+            (16, 16, (1, 1), 50, 0xff00_0000),
+            (16, 16, (2, 2), 100, 0xffff_ffff),
+        ]);
+        dir.insert_asset(Path::new("busy.xcur"), bytes.clone());
+        dir.insert_asset(Path::new("busy.cur.xcur"), bytes);
+
+        let handle = load_with_settings::<AnimatedCursor, AnimatedCursorLoaderSettings>(
+            &mut app,
+            "busy.xcur",
+            |_| {},
+        )
+        .unwrap();
+        let animated_cursors = app.world().resource::<Assets<AnimatedCursor>>();
+        let cursor = animated_cursors.get(&handle).unwrap();
+        assert_eq!(
+            cursor.animation,
+            Animation::from_frame_durations([(0, 50), (1, 100)])
+        );
+        assert_eq!(cursor.hotspots.get_or_default(1), (2, 2));
+
+        let handle = load_with_settings::<StaticCursor, StaticCursorLoaderSettings>(
+            &mut app,
+            "busy.cur.xcur",
+            |_| {},
+        )
+        .unwrap();
+        let static_cursors = app.world().resource::<Assets<StaticCursor>>();
+        let cursor = static_cursors.get(&handle).unwrap();
+        assert_eq!(cursor.hotspots.get_or_default(0), (1, 1));
+    }
+}
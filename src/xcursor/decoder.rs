@@ -0,0 +1,175 @@
+use std::{
+    fmt,
+    io::{Error as IoError, ErrorKind, Read, Seek, SeekFrom},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use super::*;
+
+/// The magic bytes at the start of every Xcursor file.
+pub(crate) const MAGIC: &[u8; 4] = b"Xcur";
+
+/// The chunk type of image chunks.
+pub(crate) const IMAGE_CHUNK_TYPE: u32 = 0xfffd_0002;
+
+/// The largest width and height of an image, in pixels.
+pub(crate) const MAX_IMAGE_SIZE: u32 = 0x7fff;
+
+#[derive(Debug)]
+pub enum DecodeError {
+    IoError(IoError),
+    InvalidMagic([u8; 4]),
+    InvalidHeaderSize(u32),
+    ChunkTypeMismatch(u32),
+    InvalidImageSize(u32, u32),
+    InvalidHotspot(u32, u32),
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<IoError> for DecodeError {
+    fn from(error: IoError) -> Self {
+        DecodeError::IoError(error)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::IoError(e) => write!(f, "IO error: {}", e),
+            DecodeError::InvalidMagic(magic) => {
+                write!(f, "invalid magic: {:?} (expected 'Xcur')", magic)
+            }
+            DecodeError::InvalidHeaderSize(size) => {
+                write!(f, "invalid header size: {} bytes", size)
+            }
+            DecodeError::ChunkTypeMismatch(chunk_type) => write!(
+                f,
+                "chunk type {:#x} does not match its table of contents entry",
+                chunk_type
+            ),
+            DecodeError::InvalidImageSize(width, height) => {
+                write!(f, "invalid image size: {}x{}", width, height)
+            }
+            DecodeError::InvalidHotspot(x, y) => {
+                write!(f, "hotspot ({}, {}) is outside of the image", x, y)
+            }
+        }
+    }
+}
+
+pub struct Decoder<R>
+where
+    R: Read + Seek,
+{
+    reader: R,
+}
+
+impl<R: Read + Seek> Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        Decoder { reader }
+    }
+
+    /// Decodes the images of the file. Chunks of other types, such as
+    /// comments, are skipped.
+    pub fn decode(&mut self) -> Result<XCursor, DecodeError> {
+        let mut magic = [0; 4];
+        self.reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(DecodeError::InvalidMagic(magic));
+        }
+
+        let header_size = self.reader.read_u32::<LittleEndian>()?;
+        if header_size < 16 {
+            return Err(DecodeError::InvalidHeaderSize(header_size));
+        }
+        let _version = self.reader.read_u32::<LittleEndian>()?;
+        let toc_count = self.reader.read_u32::<LittleEndian>()?;
+
+        self.reader.seek(SeekFrom::Start(header_size as u64))?;
+
+        let toc = (0..toc_count)
+            .map(|_| {
+                Ok((
+                    self.reader.read_u32::<LittleEndian>()?,
+                    self.reader.read_u32::<LittleEndian>()?,
+                    self.reader.read_u32::<LittleEndian>()?,
+                ))
+            })
+            .collect::<Result<Vec<_>, DecodeError>>()?;
+
+        let images = toc
+            .into_iter()
+            .filter(|(chunk_type, _, _)| *chunk_type == IMAGE_CHUNK_TYPE)
+            .map(|(_, _, position)| self.decode_image(position))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(XCursor { images })
+    }
+
+    fn decode_image(&mut self, position: u32) -> Result<XCursorImage, DecodeError> {
+        self.reader.seek(SeekFrom::Start(position as u64))?;
+
+        let _header_size = self.reader.read_u32::<LittleEndian>()?;
+        let chunk_type = self.reader.read_u32::<LittleEndian>()?;
+        if chunk_type != IMAGE_CHUNK_TYPE {
+            return Err(DecodeError::ChunkTypeMismatch(chunk_type));
+        }
+        let nominal_size = self.reader.read_u32::<LittleEndian>()?;
+        let _version = self.reader.read_u32::<LittleEndian>()?;
+        let width = self.reader.read_u32::<LittleEndian>()?;
+        let height = self.reader.read_u32::<LittleEndian>()?;
+        let xhot = self.reader.read_u32::<LittleEndian>()?;
+        let yhot = self.reader.read_u32::<LittleEndian>()?;
+        let delay = self.reader.read_u32::<LittleEndian>()?;
+
+        if width == 0 || height == 0 || width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+            return Err(DecodeError::InvalidImageSize(width, height));
+        }
+        if xhot >= width || yhot >= height {
+            return Err(DecodeError::InvalidHotspot(xhot, yhot));
+        }
+
+        // The size comes from the file, so the pixels are read incrementally
+        // rather than allocated up front, and a truncated image fails once the
+        // data runs out.
+        let len = width as u64 * height as u64 * 4;
+        let mut pixels = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut pixels)?;
+        if pixels.len() as u64 != len {
+            return Err(IoError::from(ErrorKind::UnexpectedEof).into());
+        }
+
+        // Pixels are stored as little endian ARGB, i.e. BGRA bytes, with
+        // premultiplied alpha.
+        for pixel in pixels.chunks_exact_mut(4) {
+            let [b, g, r, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            pixel.copy_from_slice(&[
+                unpremultiply(r, a),
+                unpremultiply(g, a),
+                unpremultiply(b, a),
+                a,
+            ]);
+        }
+
+        Ok(XCursorImage {
+            nominal_size,
+            width,
+            height,
+            xhot,
+            yhot,
+            delay,
+            pixels,
+        })
+    }
+}
+
+/// Converts a premultiplied color component to straight alpha.
+#[inline(always)]
+fn unpremultiply(value: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        return 0;
+    }
+    ((value as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8
+}
@@ -0,0 +1,273 @@
+use std::{
+    fmt,
+    io::{Error as IoError, Write},
+};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use image::RgbaImage;
+
+use crate::{
+    ani::AnimatedCursor,
+    cur::{decoder::decode_entry, StaticCursor},
+    variant::best_size_index,
+};
+
+use super::{
+    decoder::{IMAGE_CHUNK_TYPE, MAGIC, MAX_IMAGE_SIZE},
+    *,
+};
+
+/// The size of the file header in bytes.
+const HEADER_SIZE_BYTES: u32 = 16;
+
+/// The size of the header of an image chunk in bytes.
+const IMAGE_HEADER_SIZE_BYTES: u32 = 36;
+
+/// The size of a table of contents entry in bytes.
+const TOC_ENTRY_SIZE_BYTES: u32 = 12;
+
+const FILE_VERSION: u32 = 0x1_0000;
+
+const IMAGE_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum EncodeError {
+    IoError(IoError),
+    NoImages,
+    InvalidImageSize(u32, u32),
+    InvalidHotspot(u32, u32),
+    InvalidPixelLength(usize),
+    InvalidFrameIndex(usize),
+}
+
+impl std::error::Error for EncodeError {}
+
+impl From<IoError> for EncodeError {
+    fn from(error: IoError) -> Self {
+        EncodeError::IoError(error)
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::IoError(e) => write!(f, "IO error: {}", e),
+            EncodeError::NoImages => write!(f, "an Xcursor must have at least one image"),
+            EncodeError::InvalidImageSize(width, height) => write!(
+                f,
+                "invalid image size {}x{}, must be between 1x1 and {}x{}",
+                width, height, MAX_IMAGE_SIZE, MAX_IMAGE_SIZE
+            ),
+            EncodeError::InvalidHotspot(x, y) => {
+                write!(f, "hotspot ({}, {}) is outside of the image", x, y)
+            }
+            EncodeError::InvalidPixelLength(len) => {
+                write!(f, "invalid pixel data length: {} bytes", len)
+            }
+            EncodeError::InvalidFrameIndex(index) => {
+                write!(f, "invalid frame index in animation: {}", index)
+            }
+        }
+    }
+}
+
+impl XCursor {
+    /// Creates an Xcursor from the frames of each nominal size and the
+    /// animation that plays them.
+    ///
+    /// Atlas indices in the animation are used as indices into the frames of
+    /// each size. One repetition of the animation is stored, with each step's
+    /// duration as its delay. Xcursor animations always loop, so the
+    /// animation's repeat behavior is not kept.
+    pub fn from_frames<'a>(
+        sizes: impl IntoIterator<Item = (u32, &'a [(RgbaImage, (u16, u16))])>,
+        animation: &Animation,
+    ) -> Result<Self, EncodeError> {
+        let steps = animation.frames();
+
+        let mut images = Vec::new();
+        for (nominal_size, frames) in sizes {
+            for step in &steps {
+                let (image, (xhot, yhot)) = frames
+                    .get(step.atlas_index)
+                    .ok_or(EncodeError::InvalidFrameIndex(step.atlas_index))?;
+
+                images.push(XCursorImage {
+                    nominal_size,
+                    width: image.width(),
+                    height: image.height(),
+                    xhot: *xhot as u32,
+                    yhot: *yhot as u32,
+                    delay: step.duration.as_millis() as u32,
+                    pixels: image.as_raw().clone(),
+                });
+            }
+        }
+
+        if images.is_empty() {
+            return Err(EncodeError::NoImages);
+        }
+
+        Ok(XCursor { images })
+    }
+
+    /// Creates a static Xcursor from a .CUR file, with each entry as the
+    /// image of the nominal size that matches its largest dimension.
+    pub fn from_static_cursor(cursor: &StaticCursor) -> Result<Self, EncodeError> {
+        let images = cursor
+            .0
+            .entries()
+            .iter()
+            .map(|entry| {
+                let (image, (xhot, yhot)) = decode_entry(entry)?;
+                Ok(XCursorImage {
+                    nominal_size: image.width().max(image.height()),
+                    width: image.width(),
+                    height: image.height(),
+                    xhot: xhot as u32,
+                    yhot: yhot as u32,
+                    delay: 0,
+                    pixels: image.into_raw(),
+                })
+            })
+            .collect::<Result<Vec<_>, EncodeError>>()?;
+
+        if images.is_empty() {
+            return Err(EncodeError::NoImages);
+        }
+
+        Ok(XCursor { images })
+    }
+
+    /// Creates an animated Xcursor from an .ANI file.
+    ///
+    /// Every size found in the frames becomes a nominal size, using the entry
+    /// closest to it for frames that don't have that exact size, and the
+    /// steps of the cursor's sequence become the frames of each size.
+    ///
+    /// Returns [`EncodeError::InvalidFrameIndex`] if a frame has no entries.
+    pub fn from_animated_cursor(cursor: &AnimatedCursor) -> Result<Self, EncodeError> {
+        let mut nominal_sizes = cursor
+            .frames
+            .iter()
+            .flat_map(|f| f.entries().iter().map(|e| e.width().max(e.height())))
+            .collect::<Vec<_>>();
+        nominal_sizes.sort_unstable();
+        nominal_sizes.dedup();
+
+        let sizes = nominal_sizes
+            .iter()
+            .map(|&nominal_size| {
+                let frames = cursor
+                    .frames
+                    .iter()
+                    .enumerate()
+                    .map(|(frame_index, f)| {
+                        // A frame without entries can't be skipped, since the
+                        // sequence refers to frames by index.
+                        let i = best_size_index(
+                            f.entries().iter().map(|e| e.width().max(e.height())),
+                            nominal_size,
+                        )
+                        .ok_or(EncodeError::InvalidFrameIndex(frame_index))?;
+                        Ok(decode_entry(&f.entries()[i])?)
+                    })
+                    .collect::<Result<Vec<_>, EncodeError>>()?;
+                Ok((nominal_size, frames))
+            })
+            .collect::<Result<Vec<_>, EncodeError>>()?;
+
+        Self::from_frames(
+            sizes
+                .iter()
+                .map(|(nominal_size, frames)| (*nominal_size, frames.as_slice())),
+            &cursor.animation(),
+        )
+    }
+}
+
+pub struct Encoder<W>
+where
+    W: Write,
+{
+    writer: W,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W) -> Self {
+        Encoder { writer }
+    }
+
+    /// Writes `cursor` as an Xcursor file, with one image chunk per image.
+    pub fn encode(&mut self, cursor: &XCursor) -> Result<(), EncodeError> {
+        if cursor.images.is_empty() {
+            return Err(EncodeError::NoImages);
+        }
+
+        for image in &cursor.images {
+            if !(1..=MAX_IMAGE_SIZE).contains(&image.width)
+                || !(1..=MAX_IMAGE_SIZE).contains(&image.height)
+            {
+                return Err(EncodeError::InvalidImageSize(image.width, image.height));
+            }
+            if image.xhot >= image.width || image.yhot >= image.height {
+                return Err(EncodeError::InvalidHotspot(image.xhot, image.yhot));
+            }
+            if image.pixels.len() != (image.width * image.height * 4) as usize {
+                return Err(EncodeError::InvalidPixelLength(image.pixels.len()));
+            }
+        }
+
+        let toc_count = cursor.images.len() as u32;
+
+        self.writer.write_all(MAGIC)?;
+        self.writer.write_u32::<LittleEndian>(HEADER_SIZE_BYTES)?;
+        self.writer.write_u32::<LittleEndian>(FILE_VERSION)?;
+        self.writer.write_u32::<LittleEndian>(toc_count)?;
+
+        let mut position = HEADER_SIZE_BYTES + toc_count * TOC_ENTRY_SIZE_BYTES;
+        for image in &cursor.images {
+            self.writer.write_u32::<LittleEndian>(IMAGE_CHUNK_TYPE)?;
+            self.writer.write_u32::<LittleEndian>(image.nominal_size)?;
+            self.writer.write_u32::<LittleEndian>(position)?;
+
+            position += IMAGE_HEADER_SIZE_BYTES + image.pixels.len() as u32;
+        }
+
+        for image in &cursor.images {
+            for value in [
+                IMAGE_HEADER_SIZE_BYTES,
+                IMAGE_CHUNK_TYPE,
+                image.nominal_size,
+                IMAGE_VERSION,
+                image.width,
+                image.height,
+                image.xhot,
+                image.yhot,
+                image.delay,
+            ] {
+                self.writer.write_u32::<LittleEndian>(value)?;
+            }
+
+            // Pixels are stored as little endian ARGB, i.e. BGRA bytes, with
+            // premultiplied alpha.
+            for pixel in image.pixels.chunks_exact(4) {
+                let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+                self.writer.write_all(&[
+                    premultiply(b, a),
+                    premultiply(g, a),
+                    premultiply(r, a),
+                    a,
+                ])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts a straight alpha color component to premultiplied alpha.
+#[inline(always)]
+fn premultiply(value: u8, alpha: u8) -> u8 {
+    ((value as u32 * alpha as u32 + 127) / 255) as u8
+}
@@ -0,0 +1,223 @@
+pub mod asset;
+pub mod decoder;
+pub mod encoder;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::ani::animation::Animation;
+
+/// A decoded Xcursor file, as used by X11 cursor themes.
+///
+/// A file contains one or more images for each nominal size. Several images of
+/// the same nominal size are the frames of an animation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct XCursor {
+    /// The images, in the order they appear in the file.
+    pub images: Vec<XCursorImage>,
+}
+
+/// An image in an Xcursor file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct XCursorImage {
+    /// The nominal size of the image, in pixels. Images of the same cursor at
+    /// different resolutions have different nominal sizes.
+    pub nominal_size: u32,
+    /// The width of the image, in pixels.
+    pub width: u32,
+    /// The height of the image, in pixels.
+    pub height: u32,
+    /// The x coordinate of the hotspot.
+    pub xhot: u32,
+    /// The y coordinate of the hotspot.
+    pub yhot: u32,
+    /// How long the image is displayed for, in milliseconds, if it's a frame of
+    /// an animation.
+    pub delay: u32,
+    /// The pixels in RGBA order, row by row, with straight alpha.
+    pub pixels: Vec<u8>,
+}
+
+impl XCursorImage {
+    /// Returns the hotspot as `(x, y)`.
+    #[inline(always)]
+    pub fn hotspot(&self) -> (u16, u16) {
+        (self.xhot as u16, self.yhot as u16)
+    }
+}
+
+impl XCursor {
+    /// Returns the nominal sizes of the images, in ascending order.
+    pub fn nominal_sizes(&self) -> Vec<u32> {
+        let mut sizes = self
+            .images
+            .iter()
+            .map(|image| image.nominal_size)
+            .collect::<Vec<_>>();
+        sizes.sort_unstable();
+        sizes.dedup();
+        sizes
+    }
+
+    /// Returns the frames of the given nominal size, in order.
+    pub fn frames(&self, nominal_size: u32) -> Vec<&XCursorImage> {
+        self.images
+            .iter()
+            .filter(|image| image.nominal_size == nominal_size)
+            .collect()
+    }
+
+    /// Returns `true` if any nominal size has more than one frame.
+    pub fn is_animated(&self) -> bool {
+        self.nominal_sizes()
+            .into_iter()
+            .any(|size| self.frames(size).len() > 1)
+    }
+
+    /// Returns a looping [`Animation`] of the frames of the given nominal size,
+    /// using each frame's delay.
+    ///
+    /// Frame indices are used as atlas indices.
+    pub fn animation(&self, nominal_size: u32) -> Animation {
+        Animation::from_frame_durations(
+            self.frames(nominal_size)
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| (i, frame.delay)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use crate::{
+        testing::xcursor_bytes,
+        xcursor::{
+            decoder::{DecodeError, Decoder},
+            encoder::{EncodeError, Encoder},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_decode_static() {
+        let bytes = xcursor_bytes(&[
+            (24, 24, (4, 5), 0, 0xff00_ff00),
+            (32, 32, (6, 7), 0, 0x8080_0000),
+        ]);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert_eq!(cursor.nominal_sizes(), vec![24, 32]);
+        assert!(!cursor.is_animated());
+
+        let small = cursor.frames(24)[0];
+        assert_eq!((small.width, small.height), (24, 24));
+        assert_eq!(small.hotspot(), (4, 5));
+        assert_eq!(&small.pixels[0..4], &[0, 255, 0, 255]);
+
+        // Premultiplied alpha is converted to straight alpha.
+        let large = cursor.frames(32)[0];
+        assert_eq!(large.hotspot(), (6, 7));
+        assert_eq!(&large.pixels[0..4], &[255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_decode_animated() {
+        let bytes = xcursor_bytes(&[
+            (16, 16, (1, 1), 50, 0xff00_0000),
+            (16, 16, (1, 1), 50, 0xffff_ffff),
+            (16, 16, (2, 2), 200, 0xff00_0000),
+        ]);
+
+        let cursor = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert!(cursor.is_animated());
+        assert_eq!(cursor.frames(16).len(), 3);
+        assert_eq!(
+            cursor.animation(16),
+            Animation::from_frame_durations([(0, 50), (1, 50), (2, 200)])
+        );
+    }
+
+    #[test]
+    fn test_decode_truncated_image() {
+        let mut bytes = xcursor_bytes(&[(16, 16, (1, 1), 0, 0xff00_0000)]);
+
+        // Claim the largest image size, which is far more than the file holds.
+        bytes[44..52].copy_from_slice(&[0xff, 0x7f, 0, 0, 0xff, 0x7f, 0, 0]);
+
+        let Err(DecodeError::IoError(error)) = Decoder::new(std::io::Cursor::new(bytes)).decode()
+        else {
+            panic!("expected an IO error");
+        };
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let small = [
+            (
+                RgbaImage::from_pixel(16, 16, Rgba([255, 0, 0, 255])),
+                (1, 2),
+            ),
+            (
+                RgbaImage::from_pixel(16, 16, Rgba([0, 0, 255, 128])),
+                (3, 4),
+            ),
+        ];
+        let large = [
+            (
+                RgbaImage::from_pixel(32, 32, Rgba([255, 0, 0, 255])),
+                (2, 4),
+            ),
+            (
+                RgbaImage::from_pixel(32, 32, Rgba([0, 0, 255, 128])),
+                (6, 8),
+            ),
+        ];
+        let animation = Animation::from_frame_durations([(1, 100), (0, 250), (1, 100)]);
+
+        let cursor =
+            XCursor::from_frames([(16, small.as_slice()), (32, large.as_slice())], &animation)
+                .unwrap();
+
+        let mut bytes = Vec::new();
+        Encoder::new(&mut bytes).encode(&cursor).unwrap();
+
+        let decoded = Decoder::new(std::io::Cursor::new(bytes)).decode().unwrap();
+
+        assert_eq!(decoded, cursor);
+        assert_eq!(decoded.nominal_sizes(), vec![16, 32]);
+        assert_eq!(decoded.frames(32)[1].hotspot(), (2, 4));
+        assert_eq!(
+            decoded.animation(16),
+            Animation::from_frame_durations([(0, 100), (1, 250), (2, 100)])
+        );
+    }
+
+    #[test]
+    fn test_encode_frame_without_entries() {
+        let image = RgbaImage::new(16, 16);
+        let frame = crate::cur::StaticCursor::from_images([(&image, (0, 0))])
+            .unwrap()
+            .0;
+        let mut ani = crate::ani::AnimatedCursor::from_frames(
+            vec![frame.clone(), frame],
+            &Animation::from_frame_durations([(0, 100), (1, 100)]),
+        )
+        .unwrap();
+        ani.frames[0] = ico::IconDir::new(ico::ResourceType::Cursor);
+
+        // Frames without entries are not silently dropped.
+        assert!(matches!(
+            XCursor::from_animated_cursor(&ani),
+            Err(EncodeError::InvalidFrameIndex(0))
+        ));
+    }
+}